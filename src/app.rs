@@ -1,6 +1,25 @@
-use crate::db::{Database, QueryResult, Schema};
-use crate::state::{SavedTab, StateStore};
-use std::collections::HashSet;
+use crate::db::{
+    CancelHandle, Database, Dialect, IndexInfo, ObjectKind, QueryResult, Registry, Schema,
+    TableInfo, TransactionState, is_system_schema,
+};
+use crate::libpq::{redact_password, strip_password};
+use crate::picker::Picker;
+use crate::sql::{destructive, first_keyword, mentioned_names, split_statements, statement_at};
+use crate::state::{ConnectionSettings, SavedTab, StateStore};
+use crate::value::CellValue;
+use crate::worker::{CountHandle, QueryEvent, QueryHandle, lock_db};
+use ratatui::style::Color;
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Default hard limit on rows fetched for a single result.
+pub const DEFAULT_MAX_ROWS: usize = 100_000;
+
+const LOST_CONNECTION: &str = "The connection was lost; press F3 to reconnect";
+
+const COUNTING: &str = "Wait for the row count to stop";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Panel {
@@ -31,41 +50,98 @@ impl Panel {
 pub enum SidebarSection {
     Tables,
     Views,
+    MaterializedViews,
     Indexes,
+    Sequences,
+    Functions,
+    Types,
 }
 
 #[derive(Debug, Clone)]
 pub struct SidebarItem {
+    pub schema: Option<String>,
     pub name: String,
     pub section: SidebarSection,
     pub is_expanded: bool,
     pub children: Vec<String>,
+    /// Expandable nodes listed after the children
+    pub groups: Vec<SidebarGroup>,
 }
 
+impl SidebarItem {
+    pub fn has_children(&self) -> bool {
+        !self.children.is_empty() || !self.groups.is_empty()
+    }
+}
+
+/// An expandable node under a sidebar item, such as a table's constraints.
 #[derive(Debug, Clone)]
+pub struct SidebarGroup {
+    pub label: String,
+    pub is_expanded: bool,
+    pub children: Vec<String>,
+}
+
+#[derive(Debug)]
 pub struct EditorTab {
     pub name: String,
+    /// Index into `App::connections` of the connection the tab's queries run on
+    pub connection: usize,
     pub content: Vec<String>,
     pub cursor_line: usize,
     pub cursor_col: usize,
     pub scroll_offset: usize,
+    /// Statements of the last script run, one result per statement that ran
+    pub statements: Vec<String>,
+    pub results: Vec<QueryResult>,
+    pub selected_result: usize,
+    pub query: Option<QueryHandle>,
+    /// Byte range of a statement run on its own, highlighted briefly after it started
+    pub flash: Option<(Range<usize>, Instant)>,
 }
 
 impl EditorTab {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, connection: usize) -> Self {
         Self {
             name,
+            connection,
             content: vec![String::new()],
             cursor_line: 0,
             cursor_col: 0,
             scroll_offset: 0,
+            statements: Vec::new(),
+            results: Vec::new(),
+            selected_result: 0,
+            query: None,
+            flash: None,
         }
     }
 
+    /// True while the tab's query is executing or fetching rows.
+    pub fn is_running(&self) -> bool {
+        self.query.as_ref().is_some_and(QueryHandle::is_busy)
+    }
+
+    /// True while the selected result is still being read from an open cursor.
+    pub fn is_streaming(&self) -> bool {
+        self.query
+            .as_ref()
+            .is_some_and(|q| self.selected_result + 1 == q.statement_count)
+    }
+
     pub fn get_text(&self) -> String {
         self.content.join("\n")
     }
 
+    /// The cursor position as a byte offset into `get_text`.
+    pub fn cursor_offset(&self) -> usize {
+        let before: usize = self.content[..self.cursor_line]
+            .iter()
+            .map(|line| line.len() + 1)
+            .sum();
+        before + self.cursor_col.min(self.content[self.cursor_line].len())
+    }
+
     pub fn set_text(&mut self, text: &str) {
         self.content = text.lines().map(String::from).collect();
         if self.content.is_empty() {
@@ -172,30 +248,180 @@ impl EditorTab {
     }
 }
 
-pub struct App {
-    pub db: Database,
+/// An explicit transaction left open on the connection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenTransaction {
+    pub statements: usize,
+    /// A statement failed; the transaction can only be rolled back
+    pub failed: bool,
+}
+
+/// A modal prompt that takes all keys until answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialog {
+    QuitWithTransaction,
+    ConfirmDestructive,
+}
+
+/// Rows a destructive statement would touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowEstimate {
+    Counting,
+    Rows(u64),
+    Unknown,
+}
+
+/// A destructive statement waiting for confirmation.
+#[derive(Debug, Clone)]
+pub struct GuardedStatement {
+    pub text: String,
+    pub reason: &'static str,
+    pub estimate: RowEstimate,
+}
+
+/// Statements held back until the user confirms the destructive ones among them.
+#[derive(Debug)]
+pub struct PendingRun {
+    statements: Vec<String>,
+    flash: Option<Range<usize>>,
+    pub guarded: Vec<GuardedStatement>,
+    connection: usize,
+    /// Confirmed while the row count was still running; the statements start once it
+    /// has stopped and let go of the database
+    pub confirmed: bool,
+}
+
+/// The selected result cell's column and row, with the tables its statement mentions.
+struct RowContext {
+    column: String,
+    row: Vec<(String, CellValue)>,
+    tables: Vec<TableInfo>,
+}
+
+/// A live database connection, with the schema browser and transaction state that go
+/// with it.
+pub struct Connection {
+    pub db: Arc<Mutex<Database>>,
+    /// Connection string, or the name of the profile it came from
+    pub db_path: String,
+    /// What to open again to reconnect: the profile's `@name`, or the connection string
+    target: String,
+    /// Title bar color of a profile
+    pub color: Option<Color>,
+    pub read_only: bool,
+    pub dialect: Dialect,
+    /// Row counts for the confirmation dialog, while they run on another thread
+    counting: Option<CountHandle>,
+    /// The server dropped the connection; it needs reconnecting before it can be used
+    pub lost: bool,
+    cancel_handle: CancelHandle,
     pub schema: Schema,
-    pub running: bool,
-    pub focus: Panel,
-    pub show_help: bool,
+    pub settings: ConnectionSettings,
+
+    // Transaction state
+    /// Editor statements run inside a transaction that stays open until committed
+    pub transaction_mode: bool,
+    pub transaction: Option<OpenTransaction>,
 
     // Sidebar state
     pub sidebar_items: Vec<SidebarItem>,
+    /// Include pg_catalog and information_schema
+    pub show_system_schemas: bool,
     pub sidebar_selected: usize,
+    /// The group under the selected item that has the cursor, if any
+    pub sidebar_group: Option<usize>,
     pub sidebar_scroll: usize,
+}
+
+impl Connection {
+    fn new(
+        db: Database,
+        name: Option<String>,
+        color: Option<Color>,
+        state_store: Option<&StateStore>,
+    ) -> anyhow::Result<Self> {
+        let schema = db.load_schema()?;
+        let mut sidebar_items = App::build_sidebar_items(&schema, false);
+        if let Some(first) = sidebar_items.first_mut() {
+            first.is_expanded = true;
+        }
+        let target = name
+            .clone()
+            .filter(|name| name.starts_with('@'))
+            .unwrap_or_else(|| db.reopen_target());
+        let db_path = name.unwrap_or_else(|| db.path());
+        let read_only = db.is_read_only();
+        let dialect = db.dialect();
+        let cancel_handle = db.cancel_handle();
+        let settings = state_store
+            .and_then(|store| store.load_settings(&db_path).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            db: Arc::new(Mutex::new(db)),
+            db_path,
+            target,
+            color,
+            read_only,
+            dialect,
+            counting: None,
+            lost: false,
+            cancel_handle,
+            schema,
+            settings,
+            transaction_mode: false,
+            transaction: None,
+            sidebar_items,
+            show_system_schemas: false,
+            sidebar_selected: 0,
+            sidebar_group: None,
+            sidebar_scroll: 0,
+        })
+    }
+
+    /// Short name for the title bar and tab labels: the file or database name.
+    pub fn label(&self) -> String {
+        let path = redact_password(&self.db_path);
+        std::path::Path::new(&path)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or(path)
+    }
+
+    fn rebuild_sidebar(&mut self) {
+        self.sidebar_items = App::build_sidebar_items(&self.schema, self.show_system_schemas);
+        self.sidebar_selected = 0;
+        self.sidebar_group = None;
+        self.sidebar_scroll = 0;
+    }
+}
+
+pub struct App {
+    /// Open connections; tabs refer to them by index, so they are never removed
+    pub connections: Vec<Connection>,
+    pub running: bool,
+    pub focus: Panel,
+    pub show_help: bool,
+    pub status_message: Option<String>,
+    pub dialog: Option<Dialog>,
+    pub pending_run: Option<PendingRun>,
+    /// Connection picker opened over the app with Ctrl+O
+    pub picker: Option<Picker>,
 
     // Editor state
     pub tabs: Vec<EditorTab>,
     pub active_tab: usize,
 
-    // Results state
-    pub result: Option<QueryResult>,
+    // Results state (the result itself lives on the tab that ran the query)
     pub result_page: usize,
     pub result_scroll: usize,
     pub result_selected_row: usize,
     pub result_selected_col: usize,
     pub result_col_scroll: usize,
     pub page_size: usize,
+    pub max_rows: usize,
+    /// Backends for reconnecting
+    pub registry: Arc<Registry>,
     pub show_cell_detail: bool,
     pub show_row_detail: bool,
     pub row_detail_json: Option<String>,
@@ -207,48 +433,31 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(db: Database, state_store: Option<&StateStore>) -> anyhow::Result<Self> {
-        let schema = db.load_schema()?;
-        let sidebar_items = Self::build_sidebar_items(&schema);
-
-        // Try to load saved state
-        let (tabs, active_tab) = if let Some(store) = state_store {
-            if let Ok(Some((saved_tabs, saved_active))) = store.load_session(&db.path()) {
-                let tabs: Vec<EditorTab> = saved_tabs
-                    .into_iter()
-                    .map(|t| {
-                        let mut tab = EditorTab::new(t.name);
-                        tab.set_text(&t.content);
-                        tab
-                    })
-                    .collect();
-                let active = saved_active.min(tabs.len().saturating_sub(1));
-                (tabs, active)
-            } else {
-                (vec![EditorTab::new("Query 1".to_string())], 0)
-            }
-        } else {
-            (vec![EditorTab::new("Query 1".to_string())], 0)
-        };
-
+    pub fn new(
+        db: Database,
+        name: Option<String>,
+        color: Option<Color>,
+        state_store: Option<&StateStore>,
+    ) -> anyhow::Result<Self> {
         let mut app = Self {
-            db,
-            schema,
+            connections: Vec::new(),
             running: true,
             focus: Panel::Sidebar,
             show_help: false,
-            sidebar_items,
-            sidebar_selected: 0,
-            sidebar_scroll: 0,
-            tabs,
-            active_tab,
-            result: None,
+            status_message: None,
+            dialog: None,
+            pending_run: None,
+            picker: None,
+            tabs: Vec::new(),
+            active_tab: 0,
             result_page: 0,
             result_scroll: 0,
             result_selected_row: 0,
             result_selected_col: 0,
             result_col_scroll: 0,
             page_size: 100,
+            max_rows: DEFAULT_MAX_ROWS,
+            registry: Arc::new(Registry::default()),
             show_cell_detail: false,
             show_row_detail: false,
             row_detail_json: None,
@@ -256,75 +465,315 @@ impl App {
             json_selected: 0,
             json_scroll: 0,
         };
+        app.add_connection(db, name, color, state_store)?;
+        Ok(app)
+    }
+
+    /// Adds a connection along with its saved tabs, or an empty one, and switches to it.
+    pub fn add_connection(
+        &mut self,
+        db: Database,
+        name: Option<String>,
+        color: Option<Color>,
+        state_store: Option<&StateStore>,
+    ) -> anyhow::Result<()> {
+        let connection = Connection::new(db, name, color, state_store)?;
+        let index = self.connections.len();
+        let first = self.tabs.len();
 
-        if !app.sidebar_items.is_empty() {
-            app.sidebar_items[0].is_expanded = true;
+        // Try to load saved state
+        match state_store.and_then(|store| store.load_session(&connection.db_path).ok()) {
+            Some(Some((saved_tabs, saved_active))) if !saved_tabs.is_empty() => {
+                let count = saved_tabs.len();
+                self.tabs.extend(saved_tabs.into_iter().map(|t| {
+                    let mut tab = EditorTab::new(t.name, index);
+                    tab.set_text(&t.content);
+                    tab
+                }));
+                self.active_tab = first + saved_active.min(count - 1);
+            }
+            _ => {
+                self.tabs
+                    .push(EditorTab::new(format!("Query {}", first + 1), index));
+                self.active_tab = first;
+            }
         }
+        self.connections.push(connection);
+        self.reset_result_view();
+        Ok(())
+    }
 
-        Ok(app)
+    /// Switches to a tab of the open connection to `target`, the connection string or
+    /// `@name` it was opened with. Returns false if no such connection is open.
+    pub fn switch_to_connection(&mut self, target: &str) -> bool {
+        // Recent connections are listed without their passwords
+        let target = strip_password(target);
+        let Some(index) = self.connections.iter().position(|c| {
+            strip_password(&c.target) == target || strip_password(&c.db_path) == target
+        }) else {
+            return false;
+        };
+        match self.tabs.iter().position(|t| t.connection == index) {
+            Some(tab) => self.active_tab = tab,
+            None => {
+                self.tabs.push(EditorTab::new(
+                    format!("Query {}", self.tabs.len() + 1),
+                    index,
+                ));
+                self.active_tab = self.tabs.len() - 1;
+            }
+        }
+        self.reset_result_view();
+        true
     }
 
+    /// The connection of the active tab.
+    pub fn connection(&self) -> &Connection {
+        &self.connections[self.current_tab().connection]
+    }
+
+    pub fn connection_mut(&mut self) -> &mut Connection {
+        let index = self.current_tab().connection;
+        &mut self.connections[index]
+    }
+
+    /// Saves each connection's tabs and settings under its own path.
     pub fn save_state(&self, store: &StateStore) -> anyhow::Result<()> {
-        let tabs: Vec<SavedTab> = self
-            .tabs
+        for (index, connection) in self.connections.iter().enumerate() {
+            let tabs: Vec<(usize, &EditorTab)> = self
+                .tabs
+                .iter()
+                .enumerate()
+                .filter(|(_, tab)| tab.connection == index)
+                .collect();
+            let saved: Vec<SavedTab> = tabs
+                .iter()
+                .enumerate()
+                .map(|(i, (_, tab))| SavedTab {
+                    name: tab.name.clone(),
+                    content: tab.get_text(),
+                    position: i as i32,
+                })
+                .collect();
+            let active = tabs
+                .iter()
+                .position(|(i, _)| *i == self.active_tab)
+                .unwrap_or(0);
+
+            // A connection whose tabs were all closed keeps the session it had saved
+            if !saved.is_empty() {
+                store.save_session(&connection.db_path, &saved, active)?;
+            }
+            store.save_settings(&connection.db_path, &connection.settings)?;
+        }
+        Ok(())
+    }
+
+    /// Lists objects schema by schema, leaving out Postgres' catalogs unless `show_system`.
+    fn build_sidebar_items(schema: &Schema, show_system: bool) -> Vec<SidebarItem> {
+        let mut items = Vec::new();
+
+        let schemas: BTreeSet<Option<&str>> = schema
+            .tables
             .iter()
-            .enumerate()
-            .map(|(i, tab)| SavedTab {
-                name: tab.name.clone(),
-                content: tab.get_text(),
-                position: i as i32,
-            })
+            .chain(&schema.views)
+            .chain(&schema.materialized_views)
+            .map(|t| t.schema.as_deref())
+            .chain(schema.indexes.iter().map(|i| i.schema.as_deref()))
+            .chain(schema.sequences.iter().map(|s| s.schema.as_deref()))
+            .chain(schema.functions.iter().map(|f| f.schema.as_deref()))
+            .chain(schema.types.iter().map(|t| t.schema.as_deref()))
+            .filter(|s| show_system || !s.is_some_and(is_system_schema))
             .collect();
 
-        store.save_session(&self.db.path(), &tabs, self.active_tab)
-    }
+        for name in schemas {
+            for table in schema.tables.iter().filter(|t| t.schema.as_deref() == name) {
+                items.push(SidebarItem {
+                    schema: table.schema.clone(),
+                    name: table.name.clone(),
+                    section: SidebarSection::Tables,
+                    is_expanded: false,
+                    children: table
+                        .columns
+                        .iter()
+                        .map(|c| {
+                            let pk = if c.is_primary_key { " PK" } else { "" };
+                            let null = if c.is_nullable { "?" } else { "" };
+                            let fk = table
+                                .foreign_keys
+                                .iter()
+                                .find(|fk| fk.columns.contains(&c.name))
+                                .map(|fk| format!(" FK→{}", fk.ref_table))
+                                .unwrap_or_default();
+                            let filled = match (&c.generated, &c.default) {
+                                (Some(generated), _) => format!(" {}", generated),
+                                (None, Some(default)) => format!(" = {}", default),
+                                (None, None) => String::new(),
+                            };
+                            format!("{}: {}{}{}{}{}", c.name, c.data_type, null, pk, fk, filled)
+                        })
+                        .collect(),
+                    groups: Self::table_groups(table),
+                });
+            }
 
-    fn build_sidebar_items(schema: &Schema) -> Vec<SidebarItem> {
-        let mut items = Vec::new();
+            for view in schema.views.iter().filter(|v| v.schema.as_deref() == name) {
+                items.push(SidebarItem {
+                    schema: view.schema.clone(),
+                    name: view.name.clone(),
+                    section: SidebarSection::Views,
+                    is_expanded: false,
+                    children: view
+                        .columns
+                        .iter()
+                        .map(|c| format!("{}: {}", c.name, c.data_type))
+                        .collect(),
+                    groups: vec![],
+                });
+            }
 
-        for table in &schema.tables {
-            items.push(SidebarItem {
-                name: table.name.clone(),
-                section: SidebarSection::Tables,
-                is_expanded: false,
-                children: table
-                    .columns
-                    .iter()
-                    .map(|c| {
-                        let pk = if c.is_primary_key { " PK" } else { "" };
-                        let null = if c.is_nullable { "?" } else { "" };
-                        format!("{}: {}{}{}", c.name, c.data_type, null, pk)
-                    })
-                    .collect(),
-            });
-        }
+            for view in schema
+                .materialized_views
+                .iter()
+                .filter(|v| v.schema.as_deref() == name)
+            {
+                items.push(SidebarItem {
+                    schema: view.schema.clone(),
+                    name: view.name.clone(),
+                    section: SidebarSection::MaterializedViews,
+                    is_expanded: false,
+                    children: view
+                        .columns
+                        .iter()
+                        .map(|c| format!("{}: {}", c.name, c.data_type))
+                        .collect(),
+                    groups: vec![],
+                });
+            }
 
-        for view in &schema.views {
-            items.push(SidebarItem {
-                name: view.name.clone(),
-                section: SidebarSection::Views,
-                is_expanded: false,
-                children: view
-                    .columns
-                    .iter()
-                    .map(|c| format!("{}: {}", c.name, c.data_type))
-                    .collect(),
-            });
-        }
+            for index in schema
+                .indexes
+                .iter()
+                .filter(|i| i.schema.as_deref() == name)
+            {
+                let unique = if index.is_unique { " UNIQUE" } else { "" };
+                items.push(SidebarItem {
+                    schema: index.schema.clone(),
+                    name: format!("{}{} ({})", index.name, unique, index.table_name),
+                    section: SidebarSection::Indexes,
+                    is_expanded: false,
+                    children: Self::index_details(index),
+                    groups: vec![],
+                });
+            }
 
-        for index in &schema.indexes {
-            let unique = if index.is_unique { " UNIQUE" } else { "" };
-            items.push(SidebarItem {
-                name: format!("{}{} ({})", index.name, unique, index.table_name),
-                section: SidebarSection::Indexes,
-                is_expanded: false,
-                children: vec![],
-            });
+            for sequence in schema
+                .sequences
+                .iter()
+                .filter(|s| s.schema.as_deref() == name)
+            {
+                let last_value = match sequence.last_value {
+                    Some(value) => format!("last value {}", value),
+                    None => "not used yet".to_string(),
+                };
+                items.push(SidebarItem {
+                    schema: sequence.schema.clone(),
+                    name: sequence.name.clone(),
+                    section: SidebarSection::Sequences,
+                    is_expanded: false,
+                    children: vec![
+                        sequence.data_type.clone(),
+                        format!("start {}, increment {}", sequence.start, sequence.increment),
+                        last_value,
+                    ],
+                    groups: vec![],
+                });
+            }
+
+            for function in schema
+                .functions
+                .iter()
+                .filter(|f| f.schema.as_deref() == name)
+            {
+                let kind = match &function.result {
+                    Some(result) if function.kind == "function" => format!("returns {}", result),
+                    Some(result) => format!("{} returning {}", function.kind, result),
+                    None => function.kind.clone(),
+                };
+                items.push(SidebarItem {
+                    schema: function.schema.clone(),
+                    name: function.signature(),
+                    section: SidebarSection::Functions,
+                    is_expanded: false,
+                    children: vec![kind, format!("language {}", function.language)],
+                    groups: vec![],
+                });
+            }
+
+            for custom in schema.types.iter().filter(|t| t.schema.as_deref() == name) {
+                let kind = if custom.is_enum { "enum" } else { "composite" };
+                items.push(SidebarItem {
+                    schema: custom.schema.clone(),
+                    name: custom.name.clone(),
+                    section: SidebarSection::Types,
+                    is_expanded: false,
+                    children: std::iter::once(kind.to_string())
+                        .chain(custom.members.iter().cloned())
+                        .collect(),
+                    groups: vec![],
+                });
+            }
         }
 
         items
     }
 
+    /// Key columns of an index, then how it is built and used.
+    fn index_details(index: &IndexInfo) -> Vec<String> {
+        let mut details = index.columns.clone();
+        details.push(format!("using {}", index.method));
+        if let Some(predicate) = &index.predicate {
+            details.push(format!("where {}", predicate));
+        }
+        // Unique indexes enforce a constraint even when no query reads them
+        let usage = match index.scans {
+            Some(0) if !index.is_unique => ", unused".to_string(),
+            Some(1) => ", 1 scan".to_string(),
+            Some(scans) => format!(", {} scans", scans),
+            None => String::new(),
+        };
+        if let Some(size) = index.size {
+            details.push(format!("{}{}", format_size(size), usage));
+        }
+        details
+    }
+
+    /// A table's constraints and triggers, each list a group of its own.
+    fn table_groups(table: &TableInfo) -> Vec<SidebarGroup> {
+        let constraints = table
+            .constraints
+            .iter()
+            .map(|c| match &c.name {
+                Some(name) => format!("{}: {}", name, c.definition),
+                None => c.definition.clone(),
+            })
+            .collect();
+        let triggers = table
+            .triggers
+            .iter()
+            .map(|t| format!("{}: {}", t.name, t.timing))
+            .collect();
+        [("Constraints", constraints), ("Triggers", triggers)]
+            .into_iter()
+            .filter(|(_, children): &(_, Vec<String>)| !children.is_empty())
+            .map(|(label, children)| SidebarGroup {
+                label: format!("{} ({})", label, children.len()),
+                is_expanded: false,
+                children,
+            })
+            .collect()
+    }
+
     pub fn current_tab(&self) -> &EditorTab {
         &self.tabs[self.active_tab]
     }
@@ -333,23 +782,34 @@ impl App {
         &mut self.tabs[self.active_tab]
     }
 
+    /// Opens a tab on the active tab's connection.
     pub fn new_tab(&mut self) {
         let num = self.tabs.len() + 1;
-        self.tabs.push(EditorTab::new(format!("Query {}", num)));
+        let connection = self.current_tab().connection;
+        self.tabs
+            .push(EditorTab::new(format!("Query {}", num), connection));
         self.active_tab = self.tabs.len() - 1;
+        self.reset_result_view();
     }
 
     pub fn close_tab(&mut self) {
         if self.tabs.len() > 1 {
-            self.tabs.remove(self.active_tab);
+            let mut tab = self.tabs.remove(self.active_tab);
+            if let Some(query) = &mut tab.query
+                && query.is_busy()
+            {
+                query.cancel();
+            }
             if self.active_tab >= self.tabs.len() {
                 self.active_tab = self.tabs.len() - 1;
             }
+            self.reset_result_view();
         }
     }
 
     pub fn next_tab(&mut self) {
         self.active_tab = (self.active_tab + 1) % self.tabs.len();
+        self.reset_result_view();
     }
 
     pub fn prev_tab(&mut self) {
@@ -358,88 +818,719 @@ impl App {
         } else {
             self.active_tab - 1
         };
+        self.reset_result_view();
     }
 
     pub fn execute_query(&mut self) {
-        let sql = self.current_tab().get_text();
-        let result = self.db.execute_query(&sql);
-        self.result = Some(result);
+        let text = self.current_tab().get_text();
+        let backslash_escapes = self.connection().dialect.backslash_escapes;
+        let mut statements: Vec<String> = split_statements(&text, backslash_escapes)
+            .into_iter()
+            .map(|s| s.text)
+            .collect();
+        if statements.is_empty() {
+            // Let the database report the empty query
+            statements.push(text);
+        }
+        self.run_statements(statements, None);
+    }
+
+    /// Runs just the statement around the cursor, flashing it in the editor.
+    pub fn execute_current_statement(&mut self) {
+        let tab = self.current_tab();
+        let text = tab.get_text();
+        let backslash_escapes = self.connection().dialect.backslash_escapes;
+        let Some(statement) = statement_at(&text, tab.cursor_offset(), backslash_escapes) else {
+            self.status_message = Some("No statement under the cursor".to_string());
+            return;
+        };
+        self.run_statements(vec![statement.text], Some(statement.start..statement.end));
+    }
+
+    /// Runs statements from the editor, asking first if any of them is destructive.
+    /// `flash` is the editor range to highlight once they start.
+    fn run_statements(&mut self, statements: Vec<String>, flash: Option<Range<usize>>) {
+        let index = self.current_tab().connection;
+        if let Some(name) = self.busy_tab(index) {
+            self.status_message = Some(format!("A query is already running in {}", name));
+            return;
+        }
+        if self.connections[index].counting.is_some() {
+            self.status_message = Some(COUNTING.to_string());
+            return;
+        }
+        if self.connections[index].lost {
+            self.status_message = Some(LOST_CONNECTION.to_string());
+            return;
+        }
+        self.close_cursors(index);
+
+        // A read-only connection refuses these anyway
+        let connection = &mut self.connections[index];
+        if connection.settings.confirm_destructive && !connection.read_only {
+            let (guarded, count_queries): (Vec<_>, Vec<_>) = statements
+                .iter()
+                .filter_map(|text| {
+                    let found = destructive(text)?;
+                    let estimate = if found.count_sql.is_some() {
+                        RowEstimate::Counting
+                    } else {
+                        RowEstimate::Unknown
+                    };
+                    let statement = GuardedStatement {
+                        text: text.clone(),
+                        reason: found.reason,
+                        estimate,
+                    };
+                    Some((statement, found.count_sql))
+                })
+                .unzip();
+            if !guarded.is_empty() {
+                connection.counting = count_queries.iter().any(Option::is_some).then(|| {
+                    CountHandle::spawn(
+                        Arc::clone(&connection.db),
+                        connection.cancel_handle.clone(),
+                        count_queries,
+                    )
+                });
+                self.pending_run = Some(PendingRun {
+                    statements,
+                    flash,
+                    guarded,
+                    connection: index,
+                    confirmed: false,
+                });
+                self.dialog = Some(Dialog::ConfirmDestructive);
+                return;
+            }
+        }
+        self.start_statements(statements, flash);
+    }
+
+    /// Runs the statements held back by the confirmation dialog, or drops them. A row
+    /// count still running is stopped first, so that it doesn't hold up the statements.
+    pub fn answer_pending_run(&mut self, confirmed: bool) {
+        let Some(pending) = &mut self.pending_run else {
+            self.dialog = None;
+            return;
+        };
+        if pending.confirmed {
+            return;
+        }
+        if let Some(counting) = &self.connections[pending.connection].counting {
+            counting.stop();
+            if confirmed {
+                // poll_queries starts them once the count has stopped
+                pending.confirmed = true;
+                return;
+            }
+        }
+        self.finish_pending_run(confirmed);
+    }
+
+    fn finish_pending_run(&mut self, confirmed: bool) {
+        self.dialog = None;
+        if let Some(pending) = self.pending_run.take() {
+            if confirmed {
+                self.start_statements(pending.statements, pending.flash);
+            } else {
+                self.status_message = Some("Cancelled".to_string());
+            }
+        }
+    }
+
+    /// Turns the destructive statement confirmation on or off for this connection.
+    pub fn toggle_confirm_destructive(&mut self) {
+        let settings = &mut self.connection_mut().settings;
+        settings.confirm_destructive = !settings.confirm_destructive;
+        let confirm = settings.confirm_destructive;
+        self.status_message = Some(if confirm {
+            "Destructive statements need confirmation on this connection".to_string()
+        } else {
+            "Destructive statements run without confirmation on this connection".to_string()
+        });
+    }
+
+    fn start_statements(&mut self, statements: Vec<String>, flash: Option<Range<usize>>) {
+        let index = self.current_tab().connection;
+        let connection = &mut self.connections[index];
+        let handle = QueryHandle::spawn(
+            Arc::clone(&connection.db),
+            connection.cancel_handle.clone(),
+            statements.clone(),
+            self.page_size * 2,
+            self.max_rows,
+            connection.transaction_mode,
+        );
+        if connection.transaction_mode {
+            // The worker opens the transaction before the first statement
+            connection.transaction.get_or_insert_default();
+        }
+        let tab = self.current_tab_mut();
+        tab.statements = statements;
+        tab.results.clear();
+        tab.selected_result = 0;
+        tab.query = Some(handle);
+        tab.flash = flash.map(|range| (range, Instant::now()));
+        self.reset_result_view();
+        self.focus = Panel::Results;
+    }
+
+    /// Collects rows and results from background queries into the tabs that started them.
+    pub fn poll_queries(&mut self) {
+        for (index, connection) in self.connections.iter_mut().enumerate() {
+            let Some(counts) = connection
+                .counting
+                .as_ref()
+                .and_then(CountHandle::try_counts)
+            else {
+                continue;
+            };
+            connection.counting = None;
+            if let Some(pending) = &mut self.pending_run
+                && pending.connection == index
+            {
+                let mut counts = counts.into_iter();
+                for statement in &mut pending.guarded {
+                    let count = counts.next().flatten();
+                    if statement.estimate == RowEstimate::Counting {
+                        statement.estimate = count.map_or(RowEstimate::Unknown, RowEstimate::Rows);
+                    }
+                }
+            }
+        }
+        if let Some(pending) = &self.pending_run
+            && pending.confirmed
+            && self.connections[pending.connection].counting.is_none()
+        {
+            self.finish_pending_run(true);
+        }
+        let mut active_changed = false;
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            let connection = &mut self.connections[tab.connection];
+            while let Some(event) = tab.query.as_mut().and_then(QueryHandle::try_event) {
+                match event {
+                    QueryEvent::Rows {
+                        statement,
+                        columns,
+                        rows,
+                        parked,
+                    } => {
+                        if tab.results.len() <= statement {
+                            let mut result = QueryResult::empty(columns);
+                            result.execution_time = tab
+                                .query
+                                .as_ref()
+                                .map(|q| q.started.elapsed())
+                                .unwrap_or_default();
+                            tab.results.push(result);
+                            tab.selected_result = statement;
+                            if let Some(open) = &mut connection.transaction {
+                                open.statements += 1;
+                            }
+                            active_changed |= i == self.active_tab;
+                        }
+                        let result = &mut tab.results[statement];
+                        result.rows.extend(rows);
+                        result.row_count = result.rows.len();
+                        result.has_more = parked;
+                    }
+                    QueryEvent::Finished {
+                        statement,
+                        result: done,
+                    } => match tab.results.get_mut(statement) {
+                        // Rows were already streamed in; keep them even if fetching more failed
+                        Some(result) => {
+                            result.has_more = done.has_more;
+                            result.rows_affected = done.rows_affected;
+                            result.last_insert_rowid = done.last_insert_rowid;
+                            result.command_tag = done.command_tag;
+                            if let Some(error) = done.error {
+                                self.status_message = Some(error);
+                            }
+                        }
+                        None => {
+                            tab.results.push(done);
+                            tab.selected_result = statement;
+                            if let Some(open) = &mut connection.transaction {
+                                open.statements += 1;
+                            }
+                            active_changed |= i == self.active_tab;
+                        }
+                    },
+                    QueryEvent::Done { transaction } => {
+                        if let Some(query) = tab.query.take()
+                            && tab.results.len() < query.statement_count
+                        {
+                            self.status_message = Some(format!(
+                                "Statement {} of {} failed, the rest were skipped",
+                                tab.results.len(),
+                                query.statement_count
+                            ));
+                        }
+                        // The worker has let go of the database by now
+                        if lock_db(&connection.db).is_closed() {
+                            connection.lost = true;
+                            self.status_message = Some(LOST_CONNECTION.to_string());
+                        }
+                        match transaction {
+                            Some(TransactionState::Idle) => connection.transaction = None,
+                            Some(state) => {
+                                let open = connection.transaction.get_or_insert_with(|| {
+                                    // Opened by a BEGIN in the script; count what ran after it
+                                    let statements = tab.statements[..tab.results.len()]
+                                        .iter()
+                                        .rev()
+                                        .take_while(|s| {
+                                            !matches!(first_keyword(s).as_str(), "BEGIN" | "START")
+                                        })
+                                        .count();
+                                    OpenTransaction {
+                                        statements,
+                                        failed: false,
+                                    }
+                                });
+                                open.failed = state == TransactionState::Failed;
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+        }
+        if active_changed {
+            self.reset_result_view();
+        }
+        self.prefetch_rows();
+    }
+
+    /// Keeps one page of rows beyond the current page loaded while a cursor is open.
+    fn prefetch_rows(&mut self) {
+        let wanted = (self.result_page + 2) * self.page_size;
+        let page_size = self.page_size;
+        let tab = &mut self.tabs[self.active_tab];
+        if !tab.is_streaming() {
+            return;
+        }
+        if let (Some(query), Some(result)) = (&mut tab.query, tab.results.get(tab.selected_result))
+            && result.rows.len() < wanted
+        {
+            query.fetch_more(page_size);
+        }
+    }
+
+    /// Shows the next statement's result of a multi-statement script.
+    pub fn next_statement_result(&mut self) {
+        let tab = self.current_tab_mut();
+        if tab.selected_result + 1 < tab.results.len() {
+            tab.selected_result += 1;
+            self.reset_result_view();
+            self.prefetch_rows();
+        }
+    }
+
+    pub fn prev_statement_result(&mut self) {
+        let tab = self.current_tab_mut();
+        if tab.selected_result > 0 {
+            tab.selected_result -= 1;
+            self.reset_result_view();
+        }
+    }
+
+    /// Closes the connection's cursors left open by finished queries so that it is free
+    /// again.
+    fn close_cursors(&mut self, connection: usize) {
+        for tab in &mut self.tabs {
+            if tab.connection == connection && tab.query.as_ref().is_some_and(|q| !q.is_busy()) {
+                tab.query = None;
+            }
+        }
+    }
+
+    /// Cancels the running query, the active tab's if it has one. Returns false if nothing
+    /// was running.
+    pub fn cancel_query(&mut self) -> bool {
+        let active = self.active_tab;
+        match self
+            .tabs
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, t)| t.is_running())
+            .max_by_key(|(i, _)| *i == active)
+            .and_then(|(_, t)| t.query.as_mut())
+        {
+            Some(query) => {
+                query.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn toggle_transaction_mode(&mut self) {
+        let connection = self.connection_mut();
+        if connection.transaction_mode && connection.transaction.is_some() {
+            self.status_message =
+                Some("Commit or roll back the open transaction first".to_string());
+            return;
+        }
+        connection.transaction_mode = !connection.transaction_mode;
+        let on = connection.transaction_mode;
+        self.status_message = Some(if on {
+            "Transaction mode on: statements stay uncommitted until F8".to_string()
+        } else {
+            "Transaction mode off".to_string()
+        });
+    }
+
+    /// Commits or rolls back the active connection's open transaction. Returns false if it
+    /// is still open.
+    pub fn end_transaction(&mut self, commit: bool) -> bool {
+        self.end_transaction_on(self.current_tab().connection, commit)
+    }
+
+    fn end_transaction_on(&mut self, index: usize, commit: bool) -> bool {
+        let Some(open) = self.connections[index].transaction else {
+            self.status_message = Some("No open transaction".to_string());
+            return true;
+        };
+        if self.is_busy(index) {
+            self.status_message = Some("Wait for the running query to finish".to_string());
+            return false;
+        }
+        self.close_cursors(index);
+
+        let connection = &mut self.connections[index];
+        let db = lock_db(&connection.db);
+        let outcome = db.end_transaction(commit);
+        self.status_message = Some(match outcome {
+            Ok(()) if commit && open.failed => {
+                "The transaction had failed and was rolled back".to_string()
+            }
+            Ok(()) if commit => format!("Committed {} statements", open.statements),
+            Ok(()) => format!("Rolled back {} statements", open.statements),
+            Err(e) => format!("Failed to end transaction: {}", e),
+        });
+        if db.transaction_state() == TransactionState::Idle {
+            drop(db);
+            connection.transaction = None;
+        }
+        connection.transaction.is_none()
+    }
+
+    /// Quits, unless an open transaction needs to be committed or rolled back first.
+    pub fn request_quit(&mut self) {
+        if self.open_transaction().is_some() {
+            self.dialog = Some(Dialog::QuitWithTransaction);
+        } else {
+            self.running = false;
+        }
+    }
+
+    /// The first connection with a transaction open, which quitting has to end first.
+    pub fn open_transaction(&self) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.transaction.is_some())
+    }
+
+    /// Ends the transaction the quit dialog asks about, then quits unless another
+    /// connection has one open too.
+    pub fn end_transaction_and_quit(&mut self, commit: bool) {
+        self.dialog = None;
+        let Some(index) = self
+            .connections
+            .iter()
+            .position(|c| c.transaction.is_some())
+        else {
+            return;
+        };
+        if self.end_transaction_on(index, commit) {
+            self.request_quit();
+        }
+    }
+
+    /// The query running on the worker, the active tab's if it has one, with the name of
+    /// its tab.
+    pub fn running_query(&self) -> Option<(&str, &QueryHandle)> {
+        let running = |t: &&EditorTab| t.is_running();
+        self.tabs
+            .get(self.active_tab)
+            .filter(running)
+            .into_iter()
+            .chain(self.tabs.iter().filter(running))
+            .next()
+            .and_then(|t| t.query.as_ref().map(|q| (t.name.as_str(), q)))
+    }
+
+    /// The name of the tab running a query on the connection.
+    fn busy_tab(&self, connection: usize) -> Option<&str> {
+        self.tabs
+            .iter()
+            .find(|t| t.connection == connection && t.is_running())
+            .map(|t| t.name.as_str())
+    }
+
+    /// True while another thread holds the connection's database: a tab's query, or the
+    /// row count for the confirmation dialog.
+    fn is_busy(&self, connection: usize) -> bool {
+        self.busy_tab(connection).is_some() || self.connections[connection].counting.is_some()
+    }
+
+    pub fn result(&self) -> Option<&QueryResult> {
+        let tab = self.current_tab();
+        tab.results.get(tab.selected_result)
+    }
+
+    fn reset_result_view(&mut self) {
         self.result_page = 0;
         self.result_scroll = 0;
         self.result_selected_row = 0;
         self.result_selected_col = 0;
         self.result_col_scroll = 0;
-        self.focus = Panel::Results;
     }
 
     pub fn toggle_sidebar_expand(&mut self) {
-        if self.sidebar_selected < self.sidebar_items.len() {
-            let item = &mut self.sidebar_items[self.sidebar_selected];
-            if !item.children.is_empty() {
+        let connection = self.connection_mut();
+        if let Some(item) = connection
+            .sidebar_items
+            .get_mut(connection.sidebar_selected)
+        {
+            if let Some(group) = connection
+                .sidebar_group
+                .and_then(|g| item.groups.get_mut(g))
+            {
+                group.is_expanded = !group.is_expanded;
+            } else if item.has_children() {
                 item.is_expanded = !item.is_expanded;
+                connection.sidebar_group = None;
             }
         }
     }
 
     pub fn generate_select_query(&mut self) {
-        if let Some(item) = self.sidebar_items.get(self.sidebar_selected)
-            && item.section != SidebarSection::Indexes
-        {
-            let table_name = &item.name;
-            let columns = self.get_table_columns(table_name);
-            let cols_str = if columns.is_empty() {
-                "*".to_string()
-            } else {
-                columns
-                    .iter()
-                    .map(|c| format!("    \"{}\"", c))
-                    .collect::<Vec<_>>()
-                    .join(",\n")
-            };
-            let query = format!(
-                "SELECT\n{}\nFROM \"{}\"\nWHERE 1=1\n    -- AND condition\nLIMIT 100;",
-                cols_str, table_name
-            );
-            self.new_tab_with_query(&table_name.clone(), &query);
-        }
+        let Some(table) = self.selected_table() else {
+            return;
+        };
+        let dialect = self.connection().dialect;
+        let cols_str = if table.columns.is_empty() {
+            "*".to_string()
+        } else {
+            table
+                .columns
+                .iter()
+                .map(|c| format!("    {}", dialect.quote_identifier(&c.name)))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        };
+        let query = format!(
+            "SELECT\n{}\nFROM {}\nWHERE 1=1\n    -- AND condition\nLIMIT 100;",
+            cols_str,
+            table.qualified_name(dialect)
+        );
+        let name = table.name.clone();
+        self.new_tab_with_query(&name, &query);
     }
 
     pub fn generate_count_query(&mut self) {
-        if let Some(item) = self.sidebar_items.get(self.sidebar_selected)
-            && item.section != SidebarSection::Indexes
-        {
-            let query = format!(
-                "SELECT\n    COUNT(*) as count\nFROM \"{}\"\nWHERE 1=1\n    -- AND condition\n;",
-                item.name
-            );
-            self.new_tab_with_query(&item.name.clone(), &query);
-        }
+        let Some(table) = self.selected_table() else {
+            return;
+        };
+        let query = format!(
+            "SELECT\n    COUNT(*) as count\nFROM {}\nWHERE 1=1\n    -- AND condition\n;",
+            table.qualified_name(self.connection().dialect)
+        );
+        let name = table.name.clone();
+        self.new_tab_with_query(&name, &query);
     }
 
     pub fn generate_schema_query(&mut self) {
-        if let Some(item) = self.sidebar_items.get(self.sidebar_selected)
-            && item.section != SidebarSection::Indexes
-        {
-            let query = format!(
-                "-- Schema for table: {}\nPRAGMA table_info(\"{}\");",
-                item.name, item.name
-            );
-            self.new_tab_with_query(&item.name.clone(), &query);
+        let connection = self.connection();
+        let Some(item) = connection.sidebar_items.get(connection.sidebar_selected) else {
+            return;
+        };
+        let kind = match item.section {
+            SidebarSection::Tables => ObjectKind::Table,
+            SidebarSection::Views => ObjectKind::View,
+            SidebarSection::MaterializedViews => ObjectKind::MaterializedView,
+            SidebarSection::Sequences => ObjectKind::Sequence,
+            SidebarSection::Functions => ObjectKind::Function,
+            SidebarSection::Types => ObjectKind::Type,
+            SidebarSection::Indexes => return,
+        };
+
+        let (schema, name) = (item.schema.clone(), item.name.clone());
+        let index = self.current_tab().connection;
+        if self.is_busy(index) {
+            self.status_message =
+                Some("Cannot load a definition while a query is running".to_string());
+            return;
+        }
+        self.close_cursors(index);
+        let definition =
+            lock_db(&self.connections[index].db).definition(kind, schema.as_deref(), &name);
+        match definition {
+            Ok(definition) => self.new_tab_with_query(&name, &definition),
+            Err(e) => self.status_message = Some(format!("No definition for {}: {}", name, e)),
+        }
+    }
+
+    /// The table or view selected in the sidebar.
+    fn selected_table(&self) -> Option<&TableInfo> {
+        let connection = self.connection();
+        let item = connection.sidebar_items.get(connection.sidebar_selected)?;
+        let schema = &connection.schema;
+        let tables = match item.section {
+            SidebarSection::Tables => &schema.tables,
+            SidebarSection::Views => &schema.views,
+            SidebarSection::MaterializedViews => &schema.materialized_views,
+            _ => return None,
+        };
+        tables
+            .iter()
+            .find(|t| t.schema == item.schema && t.name == item.name)
+    }
+
+    /// Opens a tab with the row that the selected cell's foreign key points at.
+    pub fn follow_foreign_key(&mut self) {
+        let Some(RowContext {
+            column,
+            row,
+            tables,
+        }) = self.selected_row_context()
+        else {
+            return;
+        };
+        let Some(key) = tables
+            .iter()
+            .flat_map(|t| &t.foreign_keys)
+            .find(|fk| fk.columns.contains(&column))
+        else {
+            self.status_message = Some(format!("{} is not a foreign key column", column));
+            return;
+        };
+        let dialect = self.connection().dialect;
+        let conditions = match Self::key_conditions(dialect, &row, &key.columns, &key.ref_columns) {
+            Ok(conditions) => conditions,
+            Err(message) => {
+                self.status_message = Some(message);
+                return;
+            }
+        };
+        let query = format!(
+            "SELECT *\nFROM {}\nWHERE {};",
+            key.ref_qualified_name(dialect),
+            conditions
+        );
+        let name = key.ref_table.clone();
+        self.run_in_new_tab(&name, &query);
+    }
+
+    /// Opens a tab with, for each foreign key pointing at the selected cell's column, the
+    /// rows referencing the selected row.
+    pub fn find_referencing_rows(&mut self) {
+        let Some(RowContext {
+            column,
+            row,
+            tables,
+        }) = self.selected_row_context()
+        else {
+            return;
+        };
+        let dialect = self.connection().dialect;
+        let mut queries = Vec::new();
+        let mut name = None;
+        for table in &tables {
+            for child in &self.connection().schema.tables {
+                for key in &child.foreign_keys {
+                    if !key.references(table) || !key.ref_columns.contains(&column) {
+                        continue;
+                    }
+                    match Self::key_conditions(dialect, &row, &key.ref_columns, &key.columns) {
+                        Ok(conditions) => queries.push(format!(
+                            "SELECT *\nFROM {}\nWHERE {};",
+                            child.qualified_name(dialect),
+                            conditions
+                        )),
+                        Err(message) => {
+                            self.status_message = Some(message);
+                            return;
+                        }
+                    }
+                    name.get_or_insert_with(|| format!("{} refs", table.name));
+                }
+            }
+        }
+        match name {
+            Some(name) => self.run_in_new_tab(&name, &queries.join("\n\n")),
+            None => self.status_message = Some(format!("No foreign keys reference {}", column)),
         }
     }
 
-    fn get_table_columns(&self, table_name: &str) -> Vec<String> {
-        self.schema
+    fn selected_row_context(&mut self) -> Option<RowContext> {
+        let result = self.result()?;
+        let page_rows = self.get_current_page_rows(result);
+        let values = page_rows.get(self.result_selected_row)?;
+        let column = result.columns.get(self.result_selected_col)?.name.clone();
+        let row = result
+            .columns
+            .iter()
+            .map(|c| c.name.clone())
+            .zip(values.iter().cloned())
+            .collect();
+
+        let tab = self.current_tab();
+        let names = mentioned_names(tab.statements.get(tab.selected_result)?);
+        // An unqualified name means the default schema
+        let tables: Vec<TableInfo> = self
+            .connection()
+            .schema
             .tables
             .iter()
-            .chain(self.schema.views.iter())
-            .find(|t| t.name == table_name)
-            .map(|t| t.columns.iter().map(|c| c.name.clone()).collect())
-            .unwrap_or_default()
+            .filter(|t| {
+                names.contains(&t.name.to_lowercase())
+                    && t.schema.as_deref().is_none_or(|schema| {
+                        schema == "public" || names.contains(&schema.to_lowercase())
+                    })
+            })
+            .cloned()
+            .collect();
+        if tables.is_empty() {
+            self.status_message = Some("No table of the query found in the schema".to_string());
+            return None;
+        }
+        Some(RowContext {
+            column,
+            row,
+            tables,
+        })
+    }
+
+    /// `WHERE` conditions matching `to` columns against the row's values of `from`.
+    fn key_conditions(
+        dialect: Dialect,
+        row: &[(String, CellValue)],
+        from: &[String],
+        to: &[String],
+    ) -> Result<String, String> {
+        let mut conditions = Vec::new();
+        for (from, to) in from.iter().zip(to) {
+            let Some((_, value)) = row.iter().find(|(name, _)| name == from) else {
+                return Err(format!("The result doesn't include column {}", from));
+            };
+            let Some(literal) = value.to_sql_literal(dialect) else {
+                return Err(format!("{} is NULL and references nothing", from));
+            };
+            conditions.push(format!("{} = {}", dialect.quote_identifier(to), literal));
+        }
+        Ok(conditions.join("\n  AND "))
+    }
+
+    fn run_in_new_tab(&mut self, name: &str, query: &str) {
+        self.new_tab_with_query(name, query);
+        self.execute_query();
     }
 
     fn new_tab_with_query(&mut self, name: &str, query: &str) {
-        let mut tab = EditorTab::new(name.to_string());
+        let mut tab = EditorTab::new(name.to_string(), self.current_tab().connection);
         tab.set_text(query);
         self.tabs.push(tab);
         self.active_tab = self.tabs.len() - 1;
@@ -447,19 +1538,37 @@ impl App {
     }
 
     pub fn sidebar_up(&mut self) {
-        if self.sidebar_selected > 0 {
-            self.sidebar_selected -= 1;
+        let connection = self.connection_mut();
+        match connection.sidebar_group {
+            Some(0) => connection.sidebar_group = None,
+            Some(group) => connection.sidebar_group = Some(group - 1),
+            None if connection.sidebar_selected > 0 => {
+                connection.sidebar_selected -= 1;
+                // Land on the last group of an expanded item
+                let item = &connection.sidebar_items[connection.sidebar_selected];
+                connection.sidebar_group =
+                    (item.is_expanded && !item.groups.is_empty()).then(|| item.groups.len() - 1);
+            }
+            None => {}
         }
     }
 
     pub fn sidebar_down(&mut self) {
-        if self.sidebar_selected < self.sidebar_items.len().saturating_sub(1) {
-            self.sidebar_selected += 1;
+        let connection = self.connection_mut();
+        let Some(item) = connection.sidebar_items.get(connection.sidebar_selected) else {
+            return;
+        };
+        let next_group = connection.sidebar_group.map_or(0, |g| g + 1);
+        if item.is_expanded && next_group < item.groups.len() {
+            connection.sidebar_group = Some(next_group);
+        } else if connection.sidebar_selected < connection.sidebar_items.len().saturating_sub(1) {
+            connection.sidebar_selected += 1;
+            connection.sidebar_group = None;
         }
     }
 
     pub fn result_page_count(&self) -> usize {
-        match &self.result {
+        match self.result() {
             Some(r) if !r.rows.is_empty() => r.rows.len().div_ceil(self.page_size),
             _ => 1,
         }
@@ -471,6 +1580,7 @@ impl App {
             self.result_page += 1;
             self.result_scroll = 0;
         }
+        self.prefetch_rows();
     }
 
     pub fn prev_result_page(&mut self) {
@@ -480,7 +1590,7 @@ impl App {
         }
     }
 
-    pub fn get_current_page_rows<'a>(&self, result: &'a QueryResult) -> &'a [Vec<String>] {
+    pub fn get_current_page_rows<'a>(&self, result: &'a QueryResult) -> &'a [Vec<CellValue>] {
         let start = self.result_page * self.page_size;
         let end = (start + self.page_size).min(result.rows.len());
         if start < result.rows.len() {
@@ -491,12 +1601,71 @@ impl App {
     }
 
     pub fn refresh_schema(&mut self) -> anyhow::Result<()> {
-        self.schema = self.db.load_schema()?;
-        self.sidebar_items = Self::build_sidebar_items(&self.schema);
-        self.sidebar_selected = 0;
+        let index = self.current_tab().connection;
+        if self.is_busy(index) {
+            self.status_message =
+                Some("Cannot refresh schema while a query is running".to_string());
+            return Ok(());
+        }
+        self.close_cursors(index);
+        let connection = &mut self.connections[index];
+        connection.schema = lock_db(&connection.db).load_schema()?;
+        connection.rebuild_sidebar();
         Ok(())
     }
 
+    pub fn toggle_system_schemas(&mut self) {
+        let connection = self.connection_mut();
+        connection.show_system_schemas = !connection.show_system_schemas;
+        connection.rebuild_sidebar();
+        self.status_message = Some(if self.connection().show_system_schemas {
+            "Showing system schemas".to_string()
+        } else {
+            "Hiding system schemas".to_string()
+        });
+    }
+
+    /// Opens the active tab's connection again, after the server dropped it or to pick up
+    /// a changed profile. Its open transaction, if any, is lost.
+    pub fn reconnect(&mut self) {
+        let index = self.current_tab().connection;
+        if let Some(name) = self.busy_tab(index) {
+            self.status_message = Some(format!("Wait for the query in {} to finish", name));
+            return;
+        }
+        if self.connections[index].counting.is_some() {
+            self.status_message = Some(COUNTING.to_string());
+            return;
+        }
+        self.close_cursors(index);
+
+        let connection = &mut self.connections[index];
+        let label = connection.label();
+        let options = crate::Options {
+            registry: Arc::clone(&self.registry),
+            ..Default::default()
+        };
+        let reopened =
+            crate::open(&connection.target, connection.read_only, &options).and_then(|(db, _)| {
+                let schema = db.load_schema()?;
+                Ok((db, schema))
+            });
+        match reopened {
+            Ok((db, schema)) => {
+                connection.cancel_handle = db.cancel_handle();
+                *lock_db(&connection.db) = db;
+                connection.lost = false;
+                connection.transaction = None;
+                connection.schema = schema;
+                connection.rebuild_sidebar();
+                self.status_message = Some(format!("Reconnected to {}", label));
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Failed to reconnect to {}: {:#}", label, e));
+            }
+        }
+    }
+
     pub fn result_move_up(&mut self) {
         if self.result_selected_row > 0 {
             self.result_selected_row -= 1;
@@ -504,7 +1673,7 @@ impl App {
     }
 
     pub fn result_move_down(&mut self) {
-        if let Some(result) = &self.result {
+        if let Some(result) = self.result() {
             let page_rows = self.get_current_page_rows(result);
             if self.result_selected_row < page_rows.len().saturating_sub(1) {
                 self.result_selected_row += 1;
@@ -519,15 +1688,15 @@ impl App {
     }
 
     pub fn result_move_right(&mut self) {
-        if let Some(result) = &self.result
+        if let Some(result) = self.result()
             && self.result_selected_col < result.columns.len().saturating_sub(1)
         {
             self.result_selected_col += 1;
         }
     }
 
-    pub fn get_selected_cell(&self) -> Option<(&str, &str)> {
-        let result = self.result.as_ref()?;
+    pub fn get_selected_cell(&self) -> Option<(&str, &CellValue)> {
+        let result = self.result()?;
         if result.columns.is_empty() || result.rows.is_empty() {
             return None;
         }
@@ -535,7 +1704,7 @@ impl App {
         let row = page_rows.get(self.result_selected_row)?;
         let col_name = result.columns.get(self.result_selected_col)?;
         let cell_value = row.get(self.result_selected_col)?;
-        Some((col_name.as_str(), cell_value.as_str()))
+        Some((col_name.name.as_str(), cell_value))
     }
 
     pub fn toggle_cell_detail(&mut self) {
@@ -558,7 +1727,7 @@ impl App {
             self.row_detail_json = None;
             return;
         }
-        let result = match &self.result {
+        let result = match self.result() {
             Some(r) => r,
             None => return,
         };
@@ -572,16 +1741,11 @@ impl App {
         };
         let mut map = serde_json::Map::new();
         for (i, col) in result.columns.iter().enumerate() {
-            let value = row.get(i).map(|s| s.as_str()).unwrap_or("NULL");
-            // Try to parse as JSON value, fall back to string
-            let json_val = if value == "NULL" {
-                serde_json::Value::Null
-            } else if let Ok(v) = serde_json::from_str::<serde_json::Value>(value) {
-                v
-            } else {
-                serde_json::Value::String(value.to_string())
-            };
-            map.insert(col.clone(), json_val);
+            let json_val = row
+                .get(i)
+                .map(CellValue::to_json)
+                .unwrap_or(serde_json::Value::Null);
+            map.insert(col.name.clone(), json_val);
         }
         let json_str =
             serde_json::to_string_pretty(&serde_json::Value::Object(map)).unwrap_or_default();
@@ -594,7 +1758,7 @@ impl App {
     }
 
     pub fn result_move_to_end(&mut self) {
-        if let Some(result) = &self.result {
+        if let Some(result) = self.result() {
             let page_rows = self.get_current_page_rows(result);
             self.result_selected_row = page_rows.len().saturating_sub(1);
         }
@@ -620,3 +1784,18 @@ impl App {
         }
     }
 }
+
+/// Human-readable byte count, as `pg_size_pretty` would show it.
+fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 10 * 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 10.0 * 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.0} {}", size, UNITS[unit])
+}
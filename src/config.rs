@@ -0,0 +1,164 @@
+//! Named connections from `config.toml` in sqlclix's config directory.
+
+use crate::libpq::{quote_param, with_password};
+use anyhow::{Context, Result};
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profiles by name, opened as `sqlclix @name`
+    #[serde(default)]
+    pub connections: BTreeMap<String, Profile>,
+}
+
+/// A saved connection: either a connection string, or the parts of a PostgreSQL one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Connection string or file path, used as is but for the password
+    pub url: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub db: Option<String>,
+    pub user: Option<String>,
+    pub sslmode: Option<String>,
+    pub sslrootcert: Option<String>,
+    pub sslcert: Option<String>,
+    pub sslkey: Option<String>,
+    /// Environment variable holding the password
+    pub password_env: Option<String>,
+    /// Shell command printing the password
+    pub password_cmd: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    /// Title bar color, a name such as `red` or a `#rrggbb` value
+    pub color: Option<String>,
+}
+
+impl Config {
+    pub fn path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("sqlclix").join("config.toml"))
+    }
+
+    /// Reads the config file; a missing file is an empty config.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        toml::from_str(&text).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.connections.get(name).with_context(|| {
+            let path = Self::path().map(|path| path.display().to_string());
+            format!(
+                "No connection named {} in {}",
+                name,
+                path.as_deref().unwrap_or("the config file")
+            )
+        })
+    }
+}
+
+impl Profile {
+    /// The connection string to open, with the password looked up: from `password_cmd`,
+    /// or `password_env`. Anything left out is filled in as libpq would, down to the
+    /// password in `.pgpass`.
+    pub fn connection_string(&self) -> Result<String> {
+        if let Some(url) = &self.url {
+            return match self.password()? {
+                Some(password) => with_password(url, &password).with_context(|| {
+                    format!(
+                        "password_cmd and password_env need a connection URL or key=value \
+                         string, not {}",
+                        url
+                    )
+                }),
+                None => Ok(url.clone()),
+            };
+        }
+
+        let mut params = vec![];
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                params.push(format!("{}={}", key, quote_param(&value)));
+            }
+        };
+        push("host", self.host.clone());
+        push("port", self.port.map(|port| port.to_string()));
+        push("dbname", self.db.clone());
+        push("user", self.user.clone());
+        push("sslmode", self.sslmode.clone());
+        push("sslrootcert", self.sslrootcert.clone());
+        push("sslcert", self.sslcert.clone());
+        push("sslkey", self.sslkey.clone());
+        push("password", self.password()?);
+        if params.is_empty() {
+            // Every part from the environment, as with a bare `psql`
+            return Ok("postgresql://".to_string());
+        }
+
+        Ok(params.join(" "))
+    }
+
+    fn password(&self) -> Result<Option<String>> {
+        if let Some(command) = &self.password_cmd {
+            let output = shell_command(command)
+                .output()
+                .with_context(|| format!("Failed to run password_cmd: {}", command))?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "password_cmd failed ({}): {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let password = String::from_utf8(output.stdout)
+                .context("password_cmd printed something other than UTF-8")?;
+            return Ok(Some(password.trim_end_matches(['\r', '\n']).to_string()));
+        }
+
+        if let Some(var) = &self.password_env {
+            return std::env::var(var)
+                .map(Some)
+                .with_context(|| format!("password_env {} is not set", var));
+        }
+        Ok(None)
+    }
+
+    pub fn color(&self) -> Result<Option<Color>> {
+        self.color
+            .as_deref()
+            .map(|color| {
+                color
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid color: {}", color))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
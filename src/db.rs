@@ -1,7 +1,19 @@
+use crate::libpq::{TlsOptions, is_key_value, redact_password, resolve};
+use crate::sql::{
+    IndexDefinition, check_constraints, command_tag, dml_verb, first_keyword, parse_create_index,
+    qualified_name, quote_identifier, quote_literal, trigger_timing,
+};
+use crate::value::CellValue;
 use anyhow::{Context, Result};
-use rusqlite::{Connection, OpenFlags};
-use std::cell::RefCell;
+use mysql::prelude::Queryable;
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use postgres_native_tls::MakeTlsConnector;
+use rusqlite::{Connection, InterruptHandle, OpenFlags};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
@@ -10,35 +22,175 @@ pub struct ColumnInfo {
     pub data_type: String,
     pub is_nullable: bool,
     pub is_primary_key: bool,
+    /// Default expression as the database reports it
+    pub default: Option<String>,
+    /// How the database fills the column itself, e.g. `identity always` or
+    /// `generated as (price * qty)`
+    pub generated: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TableInfo {
+    /// Postgres schema; SQLite has none
+    pub schema: Option<String>,
     pub name: String,
     pub columns: Vec<ColumnInfo>,
+    pub foreign_keys: Vec<ForeignKey>,
+    /// CHECK, UNIQUE and EXCLUDE constraints
+    pub constraints: Vec<Constraint>,
+    pub triggers: Vec<Trigger>,
+}
+
+impl TableInfo {
+    pub fn qualified_name(&self, dialect: Dialect) -> String {
+        dialect.qualified_name(self.schema.as_deref(), &self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    /// Unnamed SQLite constraints have none
+    pub name: Option<String>,
+    /// The constraint as written, e.g. `CHECK (price > 0)`
+    pub definition: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub name: String,
+    /// When it fires, e.g. `BEFORE INSERT OR UPDATE`
+    pub timing: String,
+}
+
+/// A foreign key from some of a table's columns to the matching columns of another table.
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub ref_schema: Option<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+}
+
+impl ForeignKey {
+    pub fn ref_qualified_name(&self, dialect: Dialect) -> String {
+        dialect.qualified_name(self.ref_schema.as_deref(), &self.ref_table)
+    }
+
+    pub fn references(&self, table: &TableInfo) -> bool {
+        self.ref_schema == table.schema && self.ref_table == table.name
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct IndexInfo {
+    pub schema: Option<String>,
     pub name: String,
     pub table_name: String,
     pub is_unique: bool,
+    /// Indexed columns and expressions, in key order
+    pub columns: Vec<String>,
+    /// Access method such as `btree` or `gin`
+    pub method: String,
+    /// Condition of a partial index
+    pub predicate: Option<String>,
+    /// On-disk size in bytes (Postgres)
+    pub size: Option<i64>,
+    /// Index scans since statistics were last reset (Postgres)
+    pub scans: Option<i64>,
+}
+
+/// Postgres' own catalogs, hidden from the sidebar unless asked for.
+pub fn is_system_schema(schema: &str) -> bool {
+    schema == "information_schema" || schema.starts_with("pg_")
+}
+
+#[derive(Debug, Clone)]
+pub struct SequenceInfo {
+    pub schema: Option<String>,
+    pub name: String,
+    pub data_type: String,
+    pub start: i64,
+    pub increment: i64,
+    /// None until the sequence is first used
+    pub last_value: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionInfo {
+    pub schema: Option<String>,
+    pub name: String,
+    /// Argument list that tells overloads apart, e.g. `a integer, b text`
+    pub arguments: String,
+    /// Result type; procedures have none
+    pub result: Option<String>,
+    /// `function`, `procedure`, `aggregate` or `window`
+    pub kind: String,
+    pub language: String,
+}
+
+impl FunctionInfo {
+    /// Name and arguments, unique within the schema.
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.arguments)
+    }
 }
 
 #[derive(Debug, Clone)]
+pub struct TypeInfo {
+    pub schema: Option<String>,
+    pub name: String,
+    pub is_enum: bool,
+    /// Enum labels, or the fields of a composite type as `name: type`
+    pub members: Vec<String>,
+}
+
+/// Schema objects whose source can be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    /// With its indexes and triggers
+    Table,
+    View,
+    MaterializedView,
+    Sequence,
+    /// Named by its signature, see [`FunctionInfo::signature`]
+    Function,
+    Type,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Schema {
     pub tables: Vec<TableInfo>,
     pub views: Vec<TableInfo>,
     pub indexes: Vec<IndexInfo>,
+    // Postgres only
+    pub materialized_views: Vec<TableInfo>,
+    pub sequences: Vec<SequenceInfo>,
+    pub functions: Vec<FunctionInfo>,
+    pub types: Vec<TypeInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultColumn {
+    pub name: String,
+    /// Declared type as reported by the database, empty when unknown
+    pub data_type: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct QueryResult {
-    pub columns: Vec<String>,
-    pub rows: Vec<Vec<String>>,
+    pub columns: Vec<ResultColumn>,
+    pub rows: Vec<Vec<CellValue>>,
     pub row_count: usize,
     pub execution_time: Duration,
     pub error: Option<String>,
+    /// The cursor was closed before the last row was fetched
+    pub has_more: bool,
+    /// Rows changed by an INSERT, UPDATE or DELETE
+    pub rows_affected: Option<u64>,
+    /// Rowid of the last row inserted (SQLite), or its AUTO_INCREMENT id (MySQL)
+    pub last_insert_rowid: Option<i64>,
+    /// Completion tag such as `UPDATE 42` or `CREATE TABLE` (Postgres)
+    pub command_tag: Option<String>,
 }
 
 impl QueryResult {
@@ -49,71 +201,351 @@ impl QueryResult {
             row_count: 0,
             execution_time: time,
             error: Some(msg),
+            has_more: false,
+            rows_affected: None,
+            last_insert_rowid: None,
+            command_tag: None,
+        }
+    }
+
+    pub fn empty(columns: Vec<ResultColumn>) -> Self {
+        Self {
+            columns,
+            rows: vec![],
+            row_count: 0,
+            execution_time: Duration::ZERO,
+            error: None,
+            has_more: false,
+            rows_affected: None,
+            last_insert_rowid: None,
+            command_tag: None,
+        }
+    }
+
+    /// One-line outcome of a statement that changed rows or returned no result set.
+    pub fn summary(&self) -> Option<String> {
+        match (&self.command_tag, self.rows_affected) {
+            (Some(tag), _) => Some(tag.clone()),
+            (None, Some(1)) => Some("1 row affected".to_string()),
+            (None, Some(n)) => Some(format!("{} rows affected", n)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Receives each batch of rows read from a cursor and returns how many more rows to
+/// fetch. Returning 0 closes the cursor.
+pub type RowSink<'a> = dyn FnMut(&[ResultColumn], Vec<Vec<CellValue>>) -> usize + 'a;
+
+/// Interrupts a query running on another thread.
+#[derive(Clone)]
+pub struct CancelHandle(Arc<dyn Fn() + Send + Sync>);
+
+impl std::fmt::Debug for CancelHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CancelHandle")
+    }
+}
+
+impl CancelHandle {
+    /// Wraps `cancel`, which may block until the database has the request.
+    pub fn new(cancel: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(cancel))
+    }
+
+    /// Asks the database to stop the running statement, without waiting for the request
+    /// to get there.
+    pub fn cancel(&self) {
+        let cancel = Arc::clone(&self.0);
+        std::thread::spawn(move || cancel());
+    }
+
+    /// Asks the database to stop the running statement, returning once it has the request.
+    pub fn cancel_and_wait(&self) {
+        (self.0)()
+    }
+}
+
+/// Whether the connection is inside an explicit transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionState {
+    Idle,
+    Open,
+    /// A statement failed and the transaction can only be rolled back
+    Failed,
+}
+
+/// What sets a backend's SQL apart from the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    /// Name shown to the user, e.g. `PostgreSQL`
+    pub name: &'static str,
+    /// Whether `SAVEPOINT` can be used inside a transaction
+    pub savepoints: bool,
+    /// Whether `\` escapes the next character in every `'...'` string, as in MySQL,
+    /// rather than only in `E'...'` strings
+    pub backslash_escapes: bool,
+    /// Whether binary values are written `'\x..'::bytea`, as Postgres reads them, rather
+    /// than `X'..'`
+    pub bytea_literals: bool,
+    /// Character that quotes names: `"` in standard SQL, a backtick in MySQL
+    pub identifier_quote: char,
+}
+
+impl Dialect {
+    /// Quotes a name for use in SQL, doubling the quote character inside it.
+    pub fn quote_identifier(&self, name: &str) -> String {
+        let quote = self.identifier_quote;
+        let escaped = name.replace(quote, &format!("{quote}{quote}"));
+        format!("{quote}{escaped}{quote}")
+    }
+
+    /// A quoted name, prefixed with its quoted schema if it has one.
+    pub fn qualified_name(&self, schema: Option<&str>, name: &str) -> String {
+        match schema {
+            Some(schema) => format!(
+                "{}.{}",
+                self.quote_identifier(schema),
+                self.quote_identifier(name)
+            ),
+            None => self.quote_identifier(name),
         }
     }
 }
 
-// Trait for database operations
-trait DatabaseConnection {
+/// An open connection to a database. Implement this, together with [`Backend`], to
+/// add a database to sqlclix.
+pub trait DatabaseConnection: Send {
     fn load_schema(&self) -> Result<Schema>;
-    fn execute_query(&self, sql: &str) -> QueryResult;
+    /// Source of a schema object as a statement that would create it.
+    fn definition(&self, kind: ObjectKind, schema: Option<&str>, name: &str) -> Result<String>;
+    /// Runs a statement, passing rows to `sink` in batches. The returned result carries
+    /// the columns and outcome but no rows.
+    fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult;
+    fn transaction_state(&self) -> TransactionState;
+    /// Opens a transaction that later statements run in until it is ended.
+    fn begin_transaction(&self) -> Result<()>;
+    fn end_transaction(&self, commit: bool) -> Result<()>;
+    /// A handle that interrupts the statement this connection is running.
+    fn cancel_handle(&self) -> CancelHandle;
+    fn dialect(&self) -> Dialect;
     fn get_display_name(&self) -> String;
+    fn is_read_only(&self) -> bool;
+    /// True once the server has dropped the connection, which then needs reopening.
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+/// Opens connections for the connection strings it recognizes.
+pub trait Backend: Send + Sync {
+    /// URL schemes this backend opens, without the colon, e.g. `postgres`.
+    fn schemes(&self) -> &[&str];
+    /// Recognizes connection strings that don't start with one of [`Backend::schemes`],
+    /// such as a file path.
+    fn accepts(&self, _connection_string: &str) -> bool {
+        false
+    }
+    fn connect(
+        &self,
+        connection_string: &str,
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>>;
 }
 
 // SQLite implementation
 pub struct SqliteDatabase {
     conn: Connection,
+    interrupt: Arc<InterruptHandle>,
     path: String,
+    read_only: bool,
 }
 
 impl SqliteDatabase {
-    fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+    fn open<P: AsRef<Path>>(path: P, read_only: bool) -> Result<Self> {
         let path_str = path.as_ref().to_string_lossy().to_string();
-        let conn = Connection::open_with_flags(
-            &path,
-            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
-        )
-        .with_context(|| format!("Failed to open database: {}", path_str))?;
+        let flags = if read_only {
+            if !path.as_ref().exists() {
+                anyhow::bail!("Database file not found: {}", path_str);
+            }
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+        } else {
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
+        };
+        let conn = Connection::open_with_flags(&path, flags)
+            .with_context(|| format!("Failed to open database: {}", path_str))?;
+        let interrupt = Arc::new(conn.get_interrupt_handle());
 
         Ok(Self {
             conn,
+            interrupt,
             path: path_str,
+            read_only,
         })
     }
 
     fn load_tables(&self, object_type: &str) -> Result<Vec<TableInfo>> {
         let mut stmt = self.conn.prepare(
-            "SELECT name FROM sqlite_master WHERE type = ? AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            "SELECT name, sql FROM sqlite_master WHERE type = ? AND name NOT LIKE 'sqlite_%' ORDER BY name",
         )?;
 
-        let names: Vec<String> = stmt
-            .query_map([object_type], |row| row.get(0))?
+        let names: Vec<(String, Option<String>)> = stmt
+            .query_map([object_type], |row| Ok((row.get(0)?, row.get(1)?)))?
             .filter_map(|r| r.ok())
             .collect();
 
         let mut tables = Vec::new();
-        for name in names {
+        for (name, sql) in names {
             let columns = self.load_columns(&name)?;
-            tables.push(TableInfo { name, columns });
+            let foreign_keys = self.load_foreign_keys(&name)?;
+            let constraints = self.load_constraints(&name, sql.as_deref().unwrap_or_default())?;
+            let triggers = self.load_triggers(&name)?;
+            tables.push(TableInfo {
+                schema: None,
+                name,
+                columns,
+                foreign_keys,
+                constraints,
+                triggers,
+            });
         }
 
         Ok(tables)
     }
 
+    /// UNIQUE constraints come from the indexes SQLite creates for them; CHECK constraints
+    /// have no pragma, so they are read from the table's SQL.
+    fn load_constraints(&self, table_name: &str, create_sql: &str) -> Result<Vec<Constraint>> {
+        let quoted = table_name.replace('"', "\"\"");
+        let mut stmt = self
+            .conn
+            .prepare(&format!("PRAGMA index_list(\"{}\")", quoted))?;
+        let unique_indexes: Vec<String> = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(1)?, row.get::<_, String>(3)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter(|(_, origin)| origin == "u")
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut constraints = Vec::new();
+        for index in unique_indexes {
+            let columns = self.index_columns(&index)?;
+            constraints.push(Constraint {
+                // Indexes behind unnamed constraints get generated names
+                name: (!index.starts_with("sqlite_autoindex_")).then_some(index),
+                definition: format!("UNIQUE ({})", columns.join(", ")),
+            });
+        }
+
+        constraints.extend(
+            check_constraints(create_sql)
+                .into_iter()
+                .map(|(name, definition)| Constraint { name, definition }),
+        );
+        Ok(constraints)
+    }
+
+    fn load_triggers(&self, table_name: &str) -> Result<Vec<Trigger>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, sql FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ? ORDER BY name",
+        )?;
+        let triggers = stmt
+            .query_map([table_name], |row| {
+                let sql: Option<String> = row.get(1)?;
+                Ok(Trigger {
+                    name: row.get(0)?,
+                    timing: trigger_timing(sql.as_deref().unwrap_or_default()),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(triggers)
+    }
+
+    fn load_foreign_keys(&self, table_name: &str) -> Result<Vec<ForeignKey>> {
+        let sql = format!(
+            "PRAGMA foreign_key_list(\"{}\")",
+            table_name.replace('"', "\"\"")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        // One row per column, numbered by `id` per key and ordered by `seq`
+        let rows: Vec<(i64, String, String, Option<String>)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut foreign_keys: Vec<(i64, ForeignKey)> = Vec::new();
+        for (id, ref_table, from, to) in rows {
+            let index = match foreign_keys.iter().position(|(key, _)| *key == id) {
+                Some(index) => index,
+                None => {
+                    let key = ForeignKey {
+                        columns: Vec::new(),
+                        ref_schema: None,
+                        ref_table,
+                        ref_columns: Vec::new(),
+                    };
+                    foreign_keys.push((id, key));
+                    foreign_keys.len() - 1
+                }
+            };
+            let key = &mut foreign_keys[index].1;
+            key.columns.push(from);
+            key.ref_columns.extend(to);
+        }
+
+        // Without target columns the key points at the referenced table's primary key
+        for (_, key) in &mut foreign_keys {
+            if key.ref_columns.is_empty() {
+                key.ref_columns = self
+                    .load_columns(&key.ref_table)?
+                    .into_iter()
+                    .filter(|c| c.is_primary_key)
+                    .map(|c| c.name)
+                    .collect();
+            }
+        }
+
+        Ok(foreign_keys.into_iter().map(|(_, key)| key).collect())
+    }
+
     fn load_columns(&self, table_name: &str) -> Result<Vec<ColumnInfo>> {
-        let sql = format!("PRAGMA table_info(\"{}\")", table_name.replace('"', "\"\""));
+        // Unlike table_info, table_xinfo includes generated columns
+        let sql = format!(
+            "PRAGMA table_xinfo(\"{}\")",
+            table_name.replace('"', "\"\"")
+        );
         let mut stmt = self.conn.prepare(&sql)?;
 
         let columns = stmt
             .query_map([], |row| {
-                Ok(ColumnInfo {
-                    name: row.get(1)?,
-                    data_type: row.get::<_, String>(2).unwrap_or_default(),
-                    is_nullable: row.get::<_, i32>(3).unwrap_or(1) == 0,
-                    is_primary_key: row.get::<_, i32>(5).unwrap_or(0) != 0,
-                })
+                let hidden = row.get::<_, i32>(6).unwrap_or(0);
+                let generated = match hidden {
+                    2 => Some("generated virtual".to_string()),
+                    3 => Some("generated stored".to_string()),
+                    _ => None,
+                };
+                Ok((
+                    hidden,
+                    ColumnInfo {
+                        name: row.get(1)?,
+                        data_type: row.get::<_, String>(2).unwrap_or_default(),
+                        is_nullable: row.get::<_, i32>(3).unwrap_or(1) == 0,
+                        is_primary_key: row.get::<_, i32>(5).unwrap_or(0) != 0,
+                        default: row.get(4).ok().flatten(),
+                        generated,
+                    },
+                ))
             })?
             .filter_map(|r| r.ok())
+            // Hidden columns of virtual tables aren't part of the table's rows
+            .filter(|(hidden, _)| *hidden != 1)
+            .map(|(_, column)| column)
             .collect();
 
         Ok(columns)
@@ -121,65 +553,132 @@ impl SqliteDatabase {
 
     fn load_indexes(&self) -> Result<Vec<IndexInfo>> {
         let mut stmt = self.conn.prepare(
-            "SELECT name, tbl_name, sql FROM sqlite_master WHERE type = 'index' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            "SELECT name, tbl_name, sql FROM sqlite_master WHERE type = 'index' ORDER BY name",
         )?;
-
-        let indexes = stmt
-            .query_map([], |row| {
-                let sql: Option<String> = row.get(2).ok();
-                let is_unique = sql
-                    .map(|s| s.to_uppercase().contains("UNIQUE"))
-                    .unwrap_or(false);
-                Ok(IndexInfo {
-                    name: row.get(0)?,
-                    table_name: row.get(1)?,
-                    is_unique,
-                })
-            })?
+        let rows: Vec<(String, String, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
             .filter_map(|r| r.ok())
             .collect();
 
+        let mut indexes = Vec::new();
+        for (name, table_name, sql) in rows {
+            let definition = match sql {
+                Some(sql) => parse_create_index(&sql),
+                // Indexes SQLite makes for UNIQUE and PRIMARY KEY constraints have no SQL
+                None => IndexDefinition {
+                    is_unique: true,
+                    columns: self.index_columns(&name)?,
+                    predicate: None,
+                },
+            };
+            indexes.push(IndexInfo {
+                schema: None,
+                name,
+                table_name,
+                is_unique: definition.is_unique,
+                columns: definition.columns,
+                method: "btree".to_string(),
+                predicate: definition.predicate,
+                size: None,
+                scans: None,
+            });
+        }
         Ok(indexes)
     }
 
-    fn execute_query_inner(&self, sql: &str) -> Result<QueryResult> {
+    /// The key columns of an index, without the rowid and primary key columns SQLite
+    /// stores after them.
+    fn index_columns(&self, index: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name FROM pragma_index_xinfo(?) WHERE key = 1 AND name IS NOT NULL ORDER BY seqno",
+        )?;
+        let columns = stmt
+            .query_map([index], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(columns)
+    }
+
+    fn stream_query_inner(
+        &self,
+        sql: &str,
+        first_batch: usize,
+        sink: &mut RowSink,
+    ) -> Result<QueryResult> {
         let mut stmt = self.conn.prepare(sql)?;
-        let column_count = stmt.column_count();
-        let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+        let columns: Vec<ResultColumn> = stmt
+            .columns()
+            .iter()
+            .map(|c| ResultColumn {
+                name: c.name().to_string(),
+                data_type: c.decl_type().unwrap_or_default().to_string(),
+            })
+            .collect();
 
-        let mut rows = Vec::new();
         let mut query_rows = stmt.query([])?;
-
-        while let Some(row) = query_rows.next()? {
-            let mut row_data = Vec::with_capacity(column_count);
-            for i in 0..column_count {
-                let value = Self::get_column_value(row, i);
-                row_data.push(value);
+        let mut wanted = first_batch;
+        let mut batch = Vec::new();
+        let mut has_more = false;
+
+        // Step the statement only as far as the sink asks for
+        loop {
+            if wanted == 0 {
+                has_more = query_rows.next()?.is_some();
+                break;
+            }
+            let Some(row) = query_rows.next()? else {
+                if !batch.is_empty() {
+                    sink(&columns, batch);
+                }
+                break;
+            };
+            let row_data = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| Self::get_column_value(row, i, &column.data_type))
+                .collect();
+            batch.push(row_data);
+            if batch.len() >= wanted {
+                wanted = sink(&columns, std::mem::take(&mut batch));
             }
-            rows.push(row_data);
         }
 
-        let row_count = rows.len();
+        // Resetting the statement completes it, so the change counters are final
+        drop(query_rows);
 
-        Ok(QueryResult {
-            columns,
-            rows,
-            row_count,
-            execution_time: Duration::ZERO,
-            error: None,
-        })
+        let mut result = QueryResult::empty(columns);
+        result.has_more = has_more;
+        if let Some(verb) = dml_verb(sql) {
+            let changes = self.conn.changes();
+            result.rows_affected = Some(changes);
+            if matches!(verb.as_str(), "INSERT" | "REPLACE") && changes > 0 {
+                result.last_insert_rowid = Some(self.conn.last_insert_rowid());
+            }
+        }
+        Ok(result)
     }
 
-    fn get_column_value(row: &rusqlite::Row, idx: usize) -> String {
+    fn get_column_value(row: &rusqlite::Row, idx: usize, decl_type: &str) -> CellValue {
         use rusqlite::types::ValueRef;
 
+        // SQLite only has storage classes, so use the declared type to recover booleans and JSON
+        let decl_type = decl_type.to_uppercase();
         match row.get_ref(idx) {
-            Ok(ValueRef::Null) => "NULL".to_string(),
-            Ok(ValueRef::Integer(i)) => i.to_string(),
-            Ok(ValueRef::Real(f)) => f.to_string(),
-            Ok(ValueRef::Text(t)) => String::from_utf8_lossy(t).to_string(),
-            Ok(ValueRef::Blob(b)) => format!("[BLOB {} bytes]", b.len()),
-            Err(_) => "ERROR".to_string(),
+            Ok(ValueRef::Null) => CellValue::Null,
+            Ok(ValueRef::Integer(i)) if decl_type.starts_with("BOOL") => CellValue::Bool(i != 0),
+            Ok(ValueRef::Integer(i)) => CellValue::Int(i),
+            Ok(ValueRef::Real(f)) => CellValue::Float(f),
+            Ok(ValueRef::Text(t)) => {
+                let text = String::from_utf8_lossy(t).to_string();
+                if decl_type.starts_with("JSON")
+                    && let Ok(json) = serde_json::from_str(&text)
+                {
+                    return CellValue::Json(json);
+                }
+                CellValue::Text(text)
+            }
+            Ok(ValueRef::Blob(b)) => CellValue::Bytes(b.to_vec()),
+            Err(_) => CellValue::Unsupported(decl_type),
         }
     }
 }
@@ -194,10 +693,40 @@ impl DatabaseConnection for SqliteDatabase {
             tables,
             views,
             indexes,
+            ..Default::default()
         })
     }
 
-    fn execute_query(&self, sql: &str) -> QueryResult {
+    fn definition(&self, kind: ObjectKind, _schema: Option<&str>, name: &str) -> Result<String> {
+        let object_type = match kind {
+            ObjectKind::Table => "table",
+            ObjectKind::View => "view",
+            _ => anyhow::bail!("SQLite has no {:?} objects", kind),
+        };
+        let sql: String = self.conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = ? AND name = ?",
+            [object_type, name],
+            |row| row.get(0),
+        )?;
+
+        // Indexes SQLite made for constraints have no SQL of their own
+        let mut stmt = self.conn.prepare(
+            "SELECT sql FROM sqlite_master \
+             WHERE type IN ('index', 'trigger') AND tbl_name = ? AND sql IS NOT NULL \
+             ORDER BY type, name",
+        )?;
+        let dependents: Vec<String> = stmt
+            .query_map([name], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(std::iter::once(sql)
+            .chain(dependents)
+            .map(|sql| format!("{};\n", sql))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult {
         let start = Instant::now();
 
         let trimmed = sql.trim();
@@ -205,7 +734,7 @@ impl DatabaseConnection for SqliteDatabase {
             return QueryResult::error("Empty query".to_string(), start.elapsed());
         }
 
-        let result = self.execute_query_inner(trimmed);
+        let result = self.stream_query_inner(trimmed, first_batch, sink);
         let elapsed = start.elapsed();
 
         match result {
@@ -217,173 +746,879 @@ impl DatabaseConnection for SqliteDatabase {
         }
     }
 
+    fn transaction_state(&self) -> TransactionState {
+        // SQLite keeps a transaction usable after a failed statement
+        if self.conn.is_autocommit() {
+            TransactionState::Idle
+        } else {
+            TransactionState::Open
+        }
+    }
+
+    fn begin_transaction(&self) -> Result<()> {
+        self.conn.execute_batch("BEGIN")?;
+        Ok(())
+    }
+
+    fn end_transaction(&self, commit: bool) -> Result<()> {
+        self.conn
+            .execute_batch(if commit { "COMMIT" } else { "ROLLBACK" })?;
+        Ok(())
+    }
+
+    fn cancel_handle(&self) -> CancelHandle {
+        let interrupt = Arc::clone(&self.interrupt);
+        CancelHandle::new(move || interrupt.interrupt())
+    }
+
+    fn dialect(&self) -> Dialect {
+        Dialect {
+            name: "SQLite",
+            savepoints: true,
+            backslash_escapes: false,
+            bytea_literals: false,
+            identifier_quote: '"',
+        }
+    }
+
     fn get_display_name(&self) -> String {
         self.path.clone()
     }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+/// Opens SQLite files: `sqlite:` URLs, and any string no other backend recognizes.
+pub struct SqliteBackend;
+
+impl Backend for SqliteBackend {
+    fn schemes(&self) -> &[&str] {
+        &["sqlite"]
+    }
+
+    fn accepts(&self, _connection_string: &str) -> bool {
+        true
+    }
+
+    fn connect(
+        &self,
+        connection_string: &str,
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>> {
+        let path = connection_string
+            .strip_prefix("sqlite://")
+            .or_else(|| connection_string.strip_prefix("sqlite:"))
+            .unwrap_or(connection_string);
+        Ok(Box::new(SqliteDatabase::open(path, read_only)?))
+    }
 }
 
 // PostgreSQL implementation
 pub struct PostgresDatabase {
     client: RefCell<postgres::Client>,
+    cancel_token: postgres::CancelToken,
+    /// Also needed to cancel a query, which takes a connection of its own
+    tls: MakeTlsConnector,
     connection_string: String,
+    transaction: Cell<TransactionState>,
+    read_only: bool,
 }
 
 impl PostgresDatabase {
-    fn open(connection_string: &str) -> Result<Self> {
-        let client = postgres::Client::connect(connection_string, postgres::NoTls)
-            .with_context(|| format!("Failed to connect to PostgreSQL: {}", connection_string))?;
+    fn open(connection_string: &str, read_only: bool) -> Result<Self> {
+        let redacted = redact_password(connection_string);
+        // Filled in from pg_service.conf, the environment and .pgpass, as psql would be
+        let resolved = resolve(connection_string)
+            .with_context(|| format!("Invalid connection string: {}", redacted))?;
+        let (rest, tls_options) = TlsOptions::take(&resolved)
+            .with_context(|| format!("Invalid connection string: {}", redacted))?;
+        let mut config: postgres::Config = rest
+            .parse()
+            .with_context(|| format!("Invalid connection string: {}", redacted))?;
+        config.ssl_mode(tls_options.postgres_mode());
+        let tls = tls_options.connector()?;
+        if read_only {
+            // Server-side, so every transaction the session starts refuses writes
+            let options = format!(
+                "{} -c default_transaction_read_only=on",
+                config.get_options().unwrap_or_default()
+            );
+            config.options(options.trim_start());
+        }
+        let client = config.connect(tls.clone()).with_context(|| {
+            format!(
+                "Failed to connect to PostgreSQL: {}",
+                redact_password(&resolved)
+            )
+        })?;
+        let cancel_token = client.cancel_token();
 
         Ok(Self {
             client: RefCell::new(client),
+            cancel_token,
+            tls,
             connection_string: connection_string.to_string(),
+            transaction: Cell::new(TransactionState::Idle),
+            read_only,
         })
     }
 
     fn load_tables(&self, is_view: bool) -> Result<Vec<TableInfo>> {
         let table_type = if is_view { "VIEW" } else { "BASE TABLE" };
         let query = format!(
-            "SELECT table_name FROM information_schema.tables \
-             WHERE table_schema = 'public' AND table_type = '{}' \
-             ORDER BY table_name",
-            table_type
+            "SELECT table_schema, table_name FROM information_schema.tables \
+             WHERE table_type = '{}' AND {} \
+             ORDER BY table_schema, table_name",
+            table_type,
+            Self::schema_filter("table_schema")
         );
 
         let rows = self.client.borrow_mut().query(&query, &[])?;
-        let mut tables = Vec::new();
+        let mut columns = self.load_columns()?;
+        let (mut foreign_keys, mut constraints, mut triggers) = if is_view {
+            Default::default()
+        } else {
+            (
+                self.load_foreign_keys()?,
+                self.load_constraints()?,
+                self.load_triggers()?,
+            )
+        };
+        let tables = rows
+            .iter()
+            .map(|row| {
+                let schema: String = row.get(0);
+                let name: String = row.get(1);
+                let key = (schema, name);
+                let columns = columns.remove(&key).unwrap_or_default();
+                let foreign_keys = foreign_keys.remove(&key).unwrap_or_default();
+                let constraints = constraints.remove(&key).unwrap_or_default();
+                let triggers = triggers.remove(&key).unwrap_or_default();
+                TableInfo {
+                    schema: Some(key.0),
+                    name: key.1,
+                    columns,
+                    foreign_keys,
+                    constraints,
+                    triggers,
+                }
+            })
+            .collect();
 
-        for row in rows {
-            let name: String = row.get(0);
-            let columns = self.load_columns(&name)?;
-            tables.push(TableInfo { name, columns });
+        Ok(tables)
+    }
+
+    /// CHECK, UNIQUE and EXCLUDE constraints of every table, keyed by schema and table name.
+    fn load_constraints(&self) -> Result<HashMap<(String, String), Vec<Constraint>>> {
+        let query = "SELECT n.nspname, c.relname, con.conname, pg_get_constraintdef(con.oid) \
+                     FROM pg_constraint con \
+                     JOIN pg_class c ON c.oid = con.conrelid \
+                     JOIN pg_namespace n ON n.oid = c.relnamespace \
+                     WHERE con.contype IN ('c', 'u', 'x') \
+                     ORDER BY con.conname";
+
+        let rows = self.client.borrow_mut().query(query, &[])?;
+        let mut constraints: HashMap<(String, String), Vec<Constraint>> = HashMap::new();
+        for row in &rows {
+            constraints
+                .entry((row.get(0), row.get(1)))
+                .or_default()
+                .push(Constraint {
+                    name: Some(row.get(2)),
+                    definition: row.get(3),
+                });
         }
 
-        Ok(tables)
+        Ok(constraints)
     }
 
-    fn load_columns(&self, table_name: &str) -> Result<Vec<ColumnInfo>> {
-        let query = "SELECT column_name, data_type, is_nullable \
-                     FROM information_schema.columns \
-                     WHERE table_schema = 'public' AND table_name = $1 \
-                     ORDER BY ordinal_position";
+    /// Triggers of every table, keyed by schema and table name.
+    fn load_triggers(&self) -> Result<HashMap<(String, String), Vec<Trigger>>> {
+        // information_schema lists a trigger once per event it fires on
+        let query = "SELECT event_object_schema::text, event_object_table::text, trigger_name::text, \
+                        action_timing || ' ' || string_agg(event_manipulation, ' OR ') \
+                     FROM information_schema.triggers \
+                     GROUP BY 1, 2, 3, action_timing \
+                     ORDER BY 3";
+
+        let rows = self.client.borrow_mut().query(query, &[])?;
+        let mut triggers: HashMap<(String, String), Vec<Trigger>> = HashMap::new();
+        for row in &rows {
+            triggers
+                .entry((row.get(0), row.get(1)))
+                .or_default()
+                .push(Trigger {
+                    name: row.get(2),
+                    timing: row.get(3),
+                });
+        }
+
+        Ok(triggers)
+    }
+
+    /// Foreign keys of every table, keyed by schema and table name.
+    fn load_foreign_keys(&self) -> Result<HashMap<(String, String), Vec<ForeignKey>>> {
+        // Key columns come back in constraint order, paired with the columns they reference
+        let query = "SELECT n.nspname, c.relname, fn.nspname, fc.relname, \
+                        ARRAY(SELECT a.attname::text FROM unnest(con.conkey) WITH ORDINALITY k(num, ord) \
+                              JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.num \
+                              ORDER BY k.ord), \
+                        ARRAY(SELECT a.attname::text FROM unnest(con.confkey) WITH ORDINALITY k(num, ord) \
+                              JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.num \
+                              ORDER BY k.ord) \
+                     FROM pg_constraint con \
+                     JOIN pg_class c ON c.oid = con.conrelid \
+                     JOIN pg_namespace n ON n.oid = c.relnamespace \
+                     JOIN pg_class fc ON fc.oid = con.confrelid \
+                     JOIN pg_namespace fn ON fn.oid = fc.relnamespace \
+                     WHERE con.contype = 'f' \
+                     ORDER BY con.conname";
+
+        let rows = self.client.borrow_mut().query(query, &[])?;
+        let mut foreign_keys: HashMap<(String, String), Vec<ForeignKey>> = HashMap::new();
+        for row in &rows {
+            foreign_keys
+                .entry((row.get(0), row.get(1)))
+                .or_default()
+                .push(ForeignKey {
+                    columns: row.get(4),
+                    ref_schema: Some(row.get(2)),
+                    ref_table: row.get(3),
+                    ref_columns: row.get(5),
+                });
+        }
+
+        Ok(foreign_keys)
+    }
 
-        let rows = self.client.borrow_mut().query(query, &[&table_name])?;
+    /// Columns of every table and view, keyed by schema and table name.
+    fn load_columns(&self) -> Result<HashMap<(String, String), Vec<ColumnInfo>>> {
+        let query = format!(
+            "SELECT table_schema, table_name, column_name, data_type, is_nullable, \
+                column_default, identity_generation, generation_expression \
+             FROM information_schema.columns \
+             WHERE {} \
+             ORDER BY table_schema, table_name, ordinal_position",
+            Self::schema_filter("table_schema")
+        );
+
+        let rows = self.client.borrow_mut().query(&query, &[])?;
 
         // Load primary key information
-        let pk_query = "SELECT a.attname \
+        let pk_query = "SELECT n.nspname, c.relname, a.attname \
                         FROM pg_index i \
+                        JOIN pg_class c ON c.oid = i.indrelid \
+                        JOIN pg_namespace n ON n.oid = c.relnamespace \
                         JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey) \
-                        WHERE i.indrelid = $1::regclass AND i.indisprimary";
+                        WHERE i.indisprimary";
 
         let pk_rows = self
             .client
             .borrow_mut()
-            .query(pk_query, &[&table_name])
+            .query(pk_query, &[])
             .unwrap_or_default();
-        let primary_keys: std::collections::HashSet<String> = pk_rows
+        let primary_keys: HashSet<(String, String, String)> = pk_rows
             .iter()
-            .filter_map(|row| row.get::<_, Option<String>>(0))
+            .map(|row| (row.get(0), row.get(1), row.get(2)))
             .collect();
 
-        let columns = rows
-            .iter()
-            .map(|row| {
-                let name: String = row.get(0);
-                let data_type: String = row.get(1);
-                let is_nullable_str: String = row.get(2);
-                let is_nullable = is_nullable_str == "YES";
-                let is_primary_key = primary_keys.contains(&name);
+        let mut columns: HashMap<(String, String), Vec<ColumnInfo>> = HashMap::new();
+        for row in &rows {
+            let schema: String = row.get(0);
+            let table: String = row.get(1);
+            let name: String = row.get(2);
+            let data_type: String = row.get(3);
+            let is_nullable_str: String = row.get(4);
+            let is_nullable = is_nullable_str == "YES";
+            let is_primary_key =
+                primary_keys.contains(&(schema.clone(), table.clone(), name.clone()));
+            let identity: Option<String> = row.get(6);
+            let expression: Option<String> = row.get(7);
+            let generated = match (identity, expression) {
+                (Some(identity), _) => Some(format!("identity {}", identity.to_lowercase())),
+                (None, Some(expression)) => Some(format!("generated as {}", expression)),
+                (None, None) => None,
+            };
 
-                ColumnInfo {
+            columns
+                .entry((schema, table))
+                .or_default()
+                .push(ColumnInfo {
                     name,
                     data_type,
                     is_nullable,
                     is_primary_key,
-                }
-            })
-            .collect();
+                    default: row.get(5),
+                    generated,
+                });
+        }
 
         Ok(columns)
     }
 
     fn load_indexes(&self) -> Result<Vec<IndexInfo>> {
-        let query = "SELECT indexname, tablename, indexdef \
-                     FROM pg_indexes \
-                     WHERE schemaname = 'public' \
-                     ORDER BY indexname";
+        // Key columns only; INCLUDE columns come after indnkeyatts
+        let query = format!(
+            "SELECT n.nspname, ic.relname, tc.relname, i.indisunique, am.amname, \
+                ARRAY(SELECT pg_get_indexdef(i.indexrelid, k, true) \
+                      FROM generate_series(1, i.indnkeyatts) k), \
+                pg_get_expr(i.indpred, i.indrelid, true), \
+                pg_relation_size(i.indexrelid), s.idx_scan \
+             FROM pg_index i \
+             JOIN pg_class ic ON ic.oid = i.indexrelid \
+             JOIN pg_class tc ON tc.oid = i.indrelid \
+             JOIN pg_namespace n ON n.oid = ic.relnamespace \
+             JOIN pg_am am ON am.oid = ic.relam \
+             LEFT JOIN pg_stat_user_indexes s ON s.indexrelid = i.indexrelid \
+             WHERE {} \
+             ORDER BY n.nspname, ic.relname",
+            Self::schema_filter("n.nspname")
+        );
 
-        let rows = self.client.borrow_mut().query(query, &[])?;
+        let rows = self.client.borrow_mut().query(&query, &[])?;
         let indexes = rows
             .iter()
-            .map(|row| {
-                let name: String = row.get(0);
-                let table_name: String = row.get(1);
-                let indexdef: String = row.get(2);
-                let is_unique = indexdef.to_uppercase().contains("UNIQUE");
-
-                IndexInfo {
-                    name,
-                    table_name,
-                    is_unique,
-                }
+            .map(|row| IndexInfo {
+                schema: Some(row.get(0)),
+                name: row.get(1),
+                table_name: row.get(2),
+                is_unique: row.get(3),
+                method: row.get(4),
+                columns: row.get(5),
+                predicate: row.get(6),
+                size: Some(row.get(7)),
+                scans: row.get(8),
             })
             .collect();
 
         Ok(indexes)
     }
 
-    fn execute_query_inner(&self, sql: &str) -> Result<QueryResult> {
-        let rows = self.client.borrow_mut().query(sql, &[])?;
-
-        if rows.is_empty() {
-            return Ok(QueryResult {
-                columns: vec![],
-                rows: vec![],
-                row_count: 0,
-                execution_time: Duration::ZERO,
-                error: None,
-            });
-        }
+    /// Rebuilds `CREATE TABLE` from the catalog, followed by the table's own indexes,
+    /// triggers and comments.
+    fn table_definition(client: &mut postgres::Client, schema: &str, name: &str) -> Result<String> {
+        let oid: u32 = client
+            .query_one(
+                "SELECT c.oid FROM pg_class c \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE n.nspname = $1 AND c.relname = $2",
+                &[&schema, &name],
+            )?
+            .get(0);
+        let qualified = qualified_name(Some(schema), name);
+
+        let columns = client.query(
+            "SELECT a.attname, format_type(a.atttypid, a.atttypmod), a.attnotnull, \
+                pg_get_expr(d.adbin, d.adrelid), a.attidentity::text, a.attgenerated::text, \
+                col_description(a.attrelid, a.attnum) \
+             FROM pg_attribute a \
+             LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+             WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped \
+             ORDER BY a.attnum",
+            &[&oid],
+        )?;
+        // NOT NULL is part of each column; newer servers also list it as a constraint
+        let constraints = client.query(
+            "SELECT conname, pg_get_constraintdef(oid, true) FROM pg_constraint \
+             WHERE conrelid = $1 AND contype IN ('p', 'u', 'f', 'c', 'x') \
+             ORDER BY array_position(ARRAY['p', 'u', 'f', 'c', 'x'], contype::text), conname",
+            &[&oid],
+        )?;
 
-        let columns: Vec<String> = rows[0]
-            .columns()
+        let mut lines: Vec<String> = columns
             .iter()
-            .map(|col| col.name().to_string())
-            .collect();
-
-        let mut result_rows = Vec::new();
-        for row in &rows {
-            let mut row_data = Vec::new();
-            for (i, column) in row.columns().iter().enumerate() {
-                let value = Self::get_column_value(row, i, column.type_());
-                row_data.push(value);
-            }
-            result_rows.push(row_data);
-        }
+            .map(|row| {
+                let column: String = row.get(0);
+                let data_type: String = row.get(1);
+                let not_null: bool = row.get(2);
+                let default: Option<String> = row.get(3);
+                let identity: String = row.get(4);
+                let generated: String = row.get(5);
+                let mut line = format!("    {} {}", quote_identifier(&column), data_type);
+                match (identity.as_str(), generated.as_str(), default) {
+                    ("a", _, _) => line.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                    ("d", _, _) => line.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                    (_, "s", Some(expression)) => {
+                        line.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expression))
+                    }
+                    // PostgreSQL 18 computes virtual columns on read
+                    (_, "v", Some(expression)) => {
+                        line.push_str(&format!(" GENERATED ALWAYS AS ({}) VIRTUAL", expression))
+                    }
+                    (_, _, Some(default)) => line.push_str(&format!(" DEFAULT {}", default)),
+                    _ => {}
+                }
+                if not_null {
+                    line.push_str(" NOT NULL");
+                }
+                line
+            })
+            .collect();
+        lines.extend(constraints.iter().map(|row| {
+            let constraint: String = row.get(0);
+            let definition: String = row.get(1);
+            format!(
+                "    CONSTRAINT {} {}",
+                quote_identifier(&constraint),
+                definition
+            )
+        }));
+        let mut statements = vec![format!(
+            "CREATE TABLE {} (\n{}\n);",
+            qualified,
+            lines.join(",\n")
+        )];
+
+        // Indexes behind constraints are created by the constraints above
+        let indexes = client.query(
+            "SELECT pg_get_indexdef(i.indexrelid) FROM pg_index i \
+             JOIN pg_class ic ON ic.oid = i.indexrelid \
+             WHERE i.indrelid = $1 AND NOT EXISTS ( \
+                SELECT 1 FROM pg_constraint con \
+                WHERE con.conindid = i.indexrelid AND con.conrelid = i.indrelid) \
+             ORDER BY ic.relname",
+            &[&oid],
+        )?;
+        statements.extend(
+            indexes
+                .iter()
+                .map(|row| format!("{};", row.get::<_, String>(0))),
+        );
 
-        let row_count = result_rows.len();
+        let triggers = client.query(
+            "SELECT pg_get_triggerdef(oid, true) FROM pg_trigger \
+             WHERE tgrelid = $1 AND NOT tgisinternal \
+             ORDER BY tgname",
+            &[&oid],
+        )?;
+        statements.extend(
+            triggers
+                .iter()
+                .map(|row| format!("{};", row.get::<_, String>(0))),
+        );
 
-        Ok(QueryResult {
-            columns,
-            rows: result_rows,
-            row_count,
-            execution_time: Duration::ZERO,
-            error: None,
-        })
+        let comment: Option<String> = client
+            .query_one("SELECT obj_description($1, 'pg_class')", &[&oid])?
+            .get(0);
+        if let Some(comment) = comment {
+            statements.push(format!(
+                "COMMENT ON TABLE {} IS {};",
+                qualified,
+                quote_literal(&comment)
+            ));
+        }
+        for row in &columns {
+            if let Some(comment) = row.get::<_, Option<String>>(6) {
+                statements.push(format!(
+                    "COMMENT ON COLUMN {}.{} IS {};",
+                    qualified,
+                    quote_identifier(&row.get::<_, String>(0)),
+                    quote_literal(&comment)
+                ));
+            }
+        }
+
+        Ok(statements.join("\n\n"))
+    }
+
+    /// information_schema leaves materialized views out, so they come from the catalog.
+    fn load_materialized_views(&self) -> Result<Vec<TableInfo>> {
+        let query = format!(
+            "SELECT n.nspname, c.relname, \
+                ARRAY(SELECT a.attname::text FROM pg_attribute a \
+                      WHERE a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped \
+                      ORDER BY a.attnum), \
+                ARRAY(SELECT format_type(a.atttypid, a.atttypmod) FROM pg_attribute a \
+                      WHERE a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped \
+                      ORDER BY a.attnum) \
+             FROM pg_class c \
+             JOIN pg_namespace n ON n.oid = c.relnamespace \
+             WHERE c.relkind = 'm' AND {} \
+             ORDER BY n.nspname, c.relname",
+            Self::schema_filter("n.nspname")
+        );
+
+        let rows = self.client.borrow_mut().query(&query, &[])?;
+        let views = rows
+            .iter()
+            .map(|row| {
+                let names: Vec<String> = row.get(2);
+                let types: Vec<String> = row.get(3);
+                let columns = names
+                    .into_iter()
+                    .zip(types)
+                    .map(|(name, data_type)| ColumnInfo {
+                        name,
+                        data_type,
+                        is_nullable: true,
+                        is_primary_key: false,
+                        default: None,
+                        generated: None,
+                    })
+                    .collect();
+                TableInfo {
+                    schema: Some(row.get(0)),
+                    name: row.get(1),
+                    columns,
+                    foreign_keys: vec![],
+                    constraints: vec![],
+                    triggers: vec![],
+                }
+            })
+            .collect();
+
+        Ok(views)
+    }
+
+    fn load_sequences(&self) -> Result<Vec<SequenceInfo>> {
+        // last_value reads as NULL without the privilege to see it, too
+        let query = format!(
+            "SELECT schemaname, sequencename, data_type::text, start_value, increment_by, last_value \
+             FROM pg_sequences \
+             WHERE {} \
+             ORDER BY schemaname, sequencename",
+            Self::schema_filter("schemaname")
+        );
+
+        let rows = self.client.borrow_mut().query(&query, &[])?;
+        let sequences = rows
+            .iter()
+            .map(|row| SequenceInfo {
+                schema: Some(row.get(0)),
+                name: row.get(1),
+                data_type: row.get(2),
+                start: row.get(3),
+                increment: row.get(4),
+                last_value: row.get(5),
+            })
+            .collect();
+
+        Ok(sequences)
+    }
+
+    /// Functions and procedures, leaving out those that belong to an extension.
+    fn load_functions(&self) -> Result<Vec<FunctionInfo>> {
+        let query = format!(
+            "SELECT n.nspname, p.proname, pg_get_function_identity_arguments(p.oid), \
+                CASE WHEN p.prokind = 'p' THEN NULL ELSE pg_get_function_result(p.oid) END, \
+                CASE p.prokind WHEN 'p' THEN 'procedure' WHEN 'a' THEN 'aggregate' \
+                               WHEN 'w' THEN 'window' ELSE 'function' END, \
+                l.lanname \
+             FROM pg_proc p \
+             JOIN pg_namespace n ON n.oid = p.pronamespace \
+             JOIN pg_language l ON l.oid = p.prolang \
+             WHERE {} AND NOT EXISTS ( \
+                SELECT 1 FROM pg_depend d \
+                WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e') \
+             ORDER BY n.nspname, p.proname, 3",
+            Self::schema_filter("n.nspname")
+        );
+
+        let rows = self.client.borrow_mut().query(&query, &[])?;
+        let functions = rows
+            .iter()
+            .map(|row| FunctionInfo {
+                schema: Some(row.get(0)),
+                name: row.get(1),
+                arguments: row.get(2),
+                result: row.get(3),
+                kind: row.get(4),
+                language: row.get(5),
+            })
+            .collect();
+
+        Ok(functions)
+    }
+
+    /// Enum types and standalone composite types, not the row types of tables.
+    fn load_types(&self) -> Result<Vec<TypeInfo>> {
+        let query = format!(
+            "SELECT n.nspname, t.typname, t.typtype = 'e', \
+                CASE WHEN t.typtype = 'e' THEN \
+                    ARRAY(SELECT e.enumlabel::text FROM pg_enum e \
+                          WHERE e.enumtypid = t.oid ORDER BY e.enumsortorder) \
+                ELSE \
+                    ARRAY(SELECT a.attname || ': ' || format_type(a.atttypid, a.atttypmod) \
+                          FROM pg_attribute a \
+                          WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped \
+                          ORDER BY a.attnum) \
+                END \
+             FROM pg_type t \
+             JOIN pg_namespace n ON n.oid = t.typnamespace \
+             LEFT JOIN pg_class c ON c.oid = t.typrelid \
+             WHERE (t.typtype = 'e' OR (t.typtype = 'c' AND c.relkind = 'c')) AND {} \
+               AND NOT EXISTS ( \
+                SELECT 1 FROM pg_depend d \
+                WHERE d.classid = 'pg_type'::regclass AND d.objid = t.oid AND d.deptype = 'e') \
+             ORDER BY n.nspname, t.typname",
+            Self::schema_filter("n.nspname")
+        );
+
+        let rows = self.client.borrow_mut().query(&query, &[])?;
+        let types = rows
+            .iter()
+            .map(|row| TypeInfo {
+                schema: Some(row.get(0)),
+                name: row.get(1),
+                is_enum: row.get(2),
+                members: row.get(3),
+            })
+            .collect();
+
+        Ok(types)
+    }
+
+    /// Leaves out TOAST storage and other sessions' temporary schemas.
+    fn schema_filter(column: &str) -> String {
+        format!(
+            "{0} NOT LIKE 'pg\\_toast%' AND {0} NOT LIKE 'pg\\_temp\\_%'",
+            column
+        )
+    }
+
+    /// `CREATE AGGREGATE` for an aggregate, which `pg_get_functiondef` refuses, from its
+    /// `pg_aggregate` entry. Options left at their defaults are left out.
+    fn aggregate_definition(
+        client: &mut postgres::Client,
+        schema: &str,
+        oid: u32,
+    ) -> Result<String> {
+        let query = "SELECT p.proname, pg_get_function_identity_arguments(p.oid), \
+                        a.aggtransfn::text, format_type(a.aggtranstype, NULL), a.aggtransspace, \
+                        NULLIF(a.aggfinalfn::oid, 0)::regproc::text, a.aggfinalextra, \
+                        a.aggfinalmodify::text, \
+                        NULLIF(a.aggcombinefn::oid, 0)::regproc::text, \
+                        NULLIF(a.aggserialfn::oid, 0)::regproc::text, \
+                        NULLIF(a.aggdeserialfn::oid, 0)::regproc::text, \
+                        a.agginitval, \
+                        NULLIF(a.aggmtransfn::oid, 0)::regproc::text, \
+                        NULLIF(a.aggminvtransfn::oid, 0)::regproc::text, \
+                        CASE WHEN a.aggmtranstype <> 0 THEN format_type(a.aggmtranstype, NULL) END, \
+                        a.aggmtransspace, \
+                        NULLIF(a.aggmfinalfn::oid, 0)::regproc::text, a.aggmfinalextra, \
+                        a.aggmfinalmodify::text, a.aggminitval, \
+                        (SELECT o.oprname::text FROM pg_operator o WHERE o.oid = a.aggsortop), \
+                        a.aggkind::text, p.proparallel::text \
+                     FROM pg_aggregate a JOIN pg_proc p ON p.oid = a.aggfnoid \
+                     WHERE a.aggfnoid = $1";
+        let row = client.query_one(query, &[&oid])?;
+        let text = |i: usize| row.get::<_, Option<String>>(i);
+        let modify = |i: usize| match row.get::<_, String>(i).as_str() {
+            "s" => "SHAREABLE",
+            "w" => "READ_WRITE",
+            _ => "READ_ONLY",
+        };
+
+        let mut options = vec![
+            format!("SFUNC = {}", row.get::<_, String>(2)),
+            format!("STYPE = {}", row.get::<_, String>(3)),
+        ];
+        if row.get::<_, i32>(4) > 0 {
+            options.push(format!("SSPACE = {}", row.get::<_, i32>(4)));
+        }
+        if let Some(final_fn) = text(5) {
+            options.push(format!("FINALFUNC = {}", final_fn));
+            if row.get(6) {
+                options.push("FINALFUNC_EXTRA".to_string());
+            }
+            options.push(format!("FINALFUNC_MODIFY = {}", modify(7)));
+        }
+        for (option, i) in [("COMBINEFUNC", 8), ("SERIALFUNC", 9), ("DESERIALFUNC", 10)] {
+            if let Some(function) = text(i) {
+                options.push(format!("{} = {}", option, function));
+            }
+        }
+        if let Some(initial) = text(11) {
+            options.push(format!("INITCOND = {}", quote_literal(&initial)));
+        }
+        // The moving-aggregate mode, for window frames that don't start at the partition
+        if let Some(transition) = text(12) {
+            options.push(format!("MSFUNC = {}", transition));
+            options.push(format!("MINVFUNC = {}", text(13).unwrap_or_default()));
+            options.push(format!("MSTYPE = {}", text(14).unwrap_or_default()));
+            if row.get::<_, i32>(15) > 0 {
+                options.push(format!("MSSPACE = {}", row.get::<_, i32>(15)));
+            }
+            if let Some(final_fn) = text(16) {
+                options.push(format!("MFINALFUNC = {}", final_fn));
+                if row.get(17) {
+                    options.push("MFINALFUNC_EXTRA".to_string());
+                }
+                options.push(format!("MFINALFUNC_MODIFY = {}", modify(18)));
+            }
+            if let Some(initial) = text(19) {
+                options.push(format!("MINITCOND = {}", quote_literal(&initial)));
+            }
+        }
+        if let Some(operator) = text(20) {
+            options.push(format!("SORTOP = {}", operator));
+        }
+        match row.get::<_, String>(22).as_str() {
+            "s" => options.push("PARALLEL = SAFE".to_string()),
+            "r" => options.push("PARALLEL = RESTRICTED".to_string()),
+            _ => {}
+        }
+        if row.get::<_, String>(21) == "h" {
+            options.push("HYPOTHETICAL".to_string());
+        }
+
+        let arguments: String = row.get(1);
+        Ok(format!(
+            "CREATE AGGREGATE {}({}) (\n    {}\n);",
+            qualified_name(Some(schema), &row.get::<_, String>(0)),
+            // An aggregate without arguments counts rows, as count(*) does
+            if arguments.is_empty() {
+                "*"
+            } else {
+                &arguments
+            },
+            options.join(",\n    ")
+        ))
+    }
+
+    fn stream_query_inner(
+        &self,
+        sql: &str,
+        first_batch: usize,
+        sink: &mut RowSink,
+    ) -> Result<QueryResult> {
+        let mut client = self.client.borrow_mut();
+        let statement = client.prepare(sql)?;
+        let columns: Vec<ResultColumn> = statement
+            .columns()
+            .iter()
+            .map(|col| ResultColumn {
+                name: col.name().to_string(),
+                data_type: col.type_().name().to_string(),
+            })
+            .collect();
+
+        let verb = dml_verb(sql);
+
+        // Statements without a result set (DDL, VACUUM, ...) may not run inside a transaction
+        if columns.is_empty() {
+            let rows = client.execute(&statement, &[])?;
+            self.track_transaction_control(sql);
+            let mut result = QueryResult::empty(columns);
+            result.rows_affected = verb.is_some().then_some(rows);
+            result.command_tag = Some(command_tag(sql, rows));
+            return Ok(result);
+        }
+
+        let keyword = first_keyword(sql);
+        let (has_more, fetched) = if self.transaction.get() == TransactionState::Idle {
+            // Portals only live inside a transaction; fetch from one as far as the sink asks for
+            let mut transaction = client.transaction()?;
+            let portal = transaction.bind(&statement, &[])?;
+            let read = Self::read_batches(&columns, first_batch, sink, |n| {
+                // A row limit of 0 would fetch everything
+                let limit = n.min(i32::MAX as usize) as i32;
+                Ok(transaction.query_portal(&portal, limit)?)
+            })?;
+            transaction.commit()?;
+            read
+        } else if verb.is_none()
+            && matches!(keyword.as_str(), "SELECT" | "VALUES" | "TABLE" | "WITH")
+        {
+            // Inside the user's transaction a portal would need one of its own, so page
+            // through a SQL cursor instead
+            client.batch_execute(&format!(
+                "DECLARE sqlclix_cursor NO SCROLL CURSOR FOR {}",
+                sql
+            ))?;
+            let read = Self::read_batches(&columns, first_batch, sink, |n| {
+                Ok(client.query(&format!("FETCH FORWARD {} FROM sqlclix_cursor", n), &[])?)
+            })?;
+            client.batch_execute("CLOSE sqlclix_cursor")?;
+            read
+        } else {
+            // Other statements can't back a cursor; read rows as the server sends them,
+            // and dropping the iterator discards the rest
+            let mut rows = client.query_raw(&statement, std::iter::empty::<&dyn ToSql>())?;
+            Self::read_batches(&columns, first_batch, sink, |n| {
+                let mut batch = Vec::new();
+                while batch.len() < n
+                    && let Some(row) = rows.next()?
+                {
+                    batch.push(row);
+                }
+                Ok(batch)
+            })?
+        };
+
+        let mut result = QueryResult::empty(columns);
+        result.has_more = has_more;
+        // With RETURNING every changed row comes back, so the count is known once all are read
+        if verb.is_some() && !has_more {
+            result.rows_affected = Some(fetched);
+            result.command_tag = Some(command_tag(sql, fetched));
+        }
+        Ok(result)
+    }
+
+    /// Follows BEGIN/COMMIT/ROLLBACK typed into the editor, so later statements know
+    /// whether they run inside a transaction.
+    fn track_transaction_control(&self, sql: &str) {
+        let state = match first_keyword(sql).as_str() {
+            "BEGIN" | "START" => TransactionState::Open,
+            "COMMIT" | "END" | "ABORT" => TransactionState::Idle,
+            // Rolling back to a savepoint recovers a failed transaction without ending it
+            "ROLLBACK" if sql.to_uppercase().contains(" TO ") => TransactionState::Open,
+            "ROLLBACK" => TransactionState::Idle,
+            _ => return,
+        };
+        self.transaction.set(state);
+    }
+
+    /// Passes rows from `fetch`, which returns up to the given number, to the sink until
+    /// either runs out. Returns whether rows were left unread, and how many were read.
+    fn read_batches(
+        columns: &[ResultColumn],
+        first_batch: usize,
+        sink: &mut RowSink,
+        mut fetch: impl FnMut(usize) -> Result<Vec<postgres::Row>>,
+    ) -> Result<(bool, u64)> {
+        let mut wanted = first_batch;
+        let mut fetched = 0;
+        loop {
+            if wanted == 0 {
+                return Ok((!fetch(1)?.is_empty(), fetched));
+            }
+            let rows = fetch(wanted)?;
+            let exhausted = rows.len() < wanted;
+            fetched += rows.len() as u64;
+            if !rows.is_empty() {
+                wanted = sink(columns, rows.iter().map(Self::row_values).collect());
+            }
+            if exhausted {
+                return Ok((false, fetched));
+            }
+        }
+    }
+
+    fn row_values(row: &postgres::Row) -> Vec<CellValue> {
+        row.columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| Self::get_column_value(row, i, column.type_()))
+            .collect()
     }
 
     fn get_column_value(
         row: &postgres::Row,
         idx: usize,
         col_type: &postgres::types::Type,
-    ) -> String {
+    ) -> CellValue {
         use postgres::types::Type;
 
-        // Macro to try getting a value
+        // Macro to try getting a value and converting it into a cell
         macro_rules! try_type {
-            ($rust_type:ty) => {
+            ($rust_type:ty, $to_cell:expr) => {
                 match row.try_get::<_, Option<$rust_type>>(idx) {
-                    Ok(Some(v)) => return v.to_string(),
-                    Ok(None) => return "NULL".to_string(),
+                    Ok(Some(v)) => return $to_cell(v),
+                    Ok(None) => return CellValue::Null,
                     _ => (),
                 }
             };
@@ -392,103 +1627,79 @@ impl PostgresDatabase {
         // Handle specific PostgreSQL types
         match *col_type {
             // Boolean
-            Type::BOOL => try_type!(bool),
+            Type::BOOL => try_type!(bool, CellValue::Bool),
 
             // Integer types
-            Type::INT2 => try_type!(i16),
-            Type::INT4 => try_type!(i32),
-            Type::INT8 => try_type!(i64),
+            Type::INT2 => try_type!(i16, |v: i16| CellValue::Int(v.into())),
+            Type::INT4 => try_type!(i32, |v: i32| CellValue::Int(v.into())),
+            Type::INT8 => try_type!(i64, CellValue::Int),
 
             // Floating point types
-            Type::FLOAT4 => try_type!(f32),
-            Type::FLOAT8 => try_type!(f64),
+            Type::FLOAT4 => try_type!(f32, |v: f32| CellValue::Float(v.into())),
+            Type::FLOAT8 => try_type!(f64, CellValue::Float),
+
+            // Arbitrary precision, e.g. from sum() and avg()
+            Type::NUMERIC => try_type!(Numeric, |v: Numeric| CellValue::Decimal(v.0)),
 
             // Character/Text types
             Type::VARCHAR | Type::TEXT | Type::BPCHAR | Type::NAME | Type::CHAR => {
-                try_type!(String)
+                try_type!(String, CellValue::Text)
             }
 
             // Bytea (binary data)
             Type::BYTEA => {
-                match row.try_get::<_, Option<Vec<u8>>>(idx) {
-                    Ok(Some(v)) => {
-                        // Try to parse as UTF-8 string first
-                        if let Ok(s) = String::from_utf8(v.clone()) {
-                            // Try to parse as JSON
-                            if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(&s) {
-                                // Valid JSON - return it so it can be viewed in JSON viewer
-                                return json_value.to_string();
-                            }
+                try_type!(Vec<u8>, |v: Vec<u8>| {
+                    // Try to parse as UTF-8 string first
+                    match String::from_utf8(v) {
+                        // Valid JSON - keep it structured so it can be viewed in JSON viewer
+                        Ok(s) => match serde_json::from_str::<serde_json::Value>(&s) {
+                            Ok(json_value) => CellValue::Json(json_value),
                             // Valid UTF-8 but not JSON - return as string
-                            return s;
-                        }
-                        // Not valid UTF-8 - show as binary
-                        return format!("[BYTEA {} bytes]", v.len());
+                            Err(_) => CellValue::Text(s),
+                        },
+                        // Not valid UTF-8 - keep as binary
+                        Err(e) => CellValue::Bytes(e.into_bytes()),
                     }
-                    Ok(None) => return "NULL".to_string(),
-                    _ => (),
-                }
+                })
             }
 
-            // JSON types - display as string
-            Type::JSON | Type::JSONB => match row.try_get::<_, Option<serde_json::Value>>(idx) {
-                Ok(Some(v)) => return v.to_string(),
-                Ok(None) => return "NULL".to_string(),
-                _ => (),
-            },
+            // JSON types
+            Type::JSON | Type::JSONB => try_type!(serde_json::Value, CellValue::Json),
 
             // Date/Time types
             Type::TIMESTAMP => {
                 // Try NaiveDateTime (timestamp without timezone)
-                match row.try_get::<_, Option<chrono::NaiveDateTime>>(idx) {
-                    Ok(Some(v)) => return v.format("%Y-%m-%dT%H:%M:%S.%3f").to_string(),
-                    Ok(None) => return "NULL".to_string(),
-                    _ => (),
-                }
+                try_type!(chrono::NaiveDateTime, CellValue::Timestamp);
                 // Fallback to string
-                try_type!(String)
+                try_type!(String, CellValue::Text)
             }
 
             Type::TIMESTAMPTZ => {
                 // Try DateTime<Utc> (timestamp with timezone)
-                match row.try_get::<_, Option<chrono::DateTime<chrono::Utc>>>(idx) {
-                    Ok(Some(v)) => {
-                        return v
-                            .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
-                            .to_string();
-                    }
-                    Ok(None) => return "NULL".to_string(),
-                    _ => (),
-                }
+                try_type!(chrono::DateTime<chrono::Utc>, CellValue::TimestampTz);
                 // Fallback to string
-                try_type!(String)
+                try_type!(String, CellValue::Text)
             }
 
             Type::DATE => {
                 // Try NaiveDate
-                match row.try_get::<_, Option<chrono::NaiveDate>>(idx) {
-                    Ok(Some(v)) => return v.format("%Y-%m-%d").to_string(),
-                    Ok(None) => return "NULL".to_string(),
-                    _ => (),
-                }
-                try_type!(String)
+                try_type!(chrono::NaiveDate, CellValue::Date);
+                try_type!(String, CellValue::Text)
             }
 
             Type::TIME | Type::TIMETZ => {
                 // Try NaiveTime
-                match row.try_get::<_, Option<chrono::NaiveTime>>(idx) {
-                    Ok(Some(v)) => return v.format("%H:%M:%S").to_string(),
-                    Ok(None) => return "NULL".to_string(),
-                    _ => (),
-                }
-                try_type!(String)
+                try_type!(chrono::NaiveTime, CellValue::Time);
+                try_type!(String, CellValue::Text)
             }
 
             // UUID
-            Type::UUID => try_type!(String),
+            Type::UUID => try_type!(String, CellValue::Text),
 
             // Network types
-            Type::INET | Type::CIDR | Type::MACADDR | Type::MACADDR8 => try_type!(String),
+            Type::INET | Type::CIDR | Type::MACADDR | Type::MACADDR8 => {
+                try_type!(String, CellValue::Text)
+            }
 
             // Geometric types
             Type::POINT
@@ -497,17 +1708,17 @@ impl PostgresDatabase {
             | Type::BOX
             | Type::PATH
             | Type::POLYGON
-            | Type::CIRCLE => try_type!(String),
+            | Type::CIRCLE => try_type!(String, CellValue::Text),
 
             // Bit string types
-            Type::BIT | Type::VARBIT => try_type!(String),
+            Type::BIT | Type::VARBIT => try_type!(String, CellValue::Text),
 
             // Money
-            Type::MONEY => try_type!(String),
+            Type::MONEY => try_type!(String, CellValue::Text),
 
             // OID types
-            Type::OID
-            | Type::REGPROC
+            Type::OID => try_type!(u32, |v: u32| CellValue::Int(v.into())),
+            Type::REGPROC
             | Type::REGPROCEDURE
             | Type::REGOPER
             | Type::REGOPERATOR
@@ -516,38 +1727,97 @@ impl PostgresDatabase {
             | Type::REGROLE
             | Type::REGNAMESPACE
             | Type::REGCONFIG
-            | Type::REGDICTIONARY => try_type!(i32),
+            | Type::REGDICTIONARY => try_type!(i32, |v: i32| CellValue::Int(v.into())),
 
             // Unknown or other types
             _ => {
                 // Try common fallbacks in order
 
                 // 1. Try String (works for many types including arrays, enums, etc.)
-                try_type!(String);
+                try_type!(String, CellValue::Text);
 
                 // 2. Try i64 (numeric types)
-                try_type!(i64);
+                try_type!(i64, CellValue::Int);
 
                 // 3. Try f64 (decimal/numeric types)
-                try_type!(f64);
+                try_type!(f64, CellValue::Float);
 
                 // 4. Try bool
-                try_type!(bool);
+                try_type!(bool, CellValue::Bool);
 
                 // 5. Try JSON value (for json/jsonb variants)
-                if let Ok(Some(v)) = row.try_get::<_, Option<serde_json::Value>>(idx) {
-                    return v.to_string();
-                } else if let Ok(None) = row.try_get::<_, Option<serde_json::Value>>(idx) {
-                    return "NULL".to_string();
-                }
+                try_type!(serde_json::Value, CellValue::Json);
+            }
+        }
+
+        // If nothing works, show unsupported
+        CellValue::Unsupported(col_type.name().to_string())
+    }
+}
 
-                // If nothing works, show unsupported
-                return format!("[{} UNSUPPORTED]", col_type.name());
+/// A NUMERIC in its text form. The driver has no decoding for it that keeps every digit.
+struct Numeric(String);
+
+impl<'a> postgres::types::FromSql<'a> for Numeric {
+    fn from_sql(
+        _: &postgres::types::Type,
+        raw: &'a [u8],
+    ) -> std::result::Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        // Base-10000 digits after a header of digit count, weight of the first digit,
+        // sign and display scale, all 16-bit big-endian
+        let word =
+            |i: usize| -> std::result::Result<u16, Box<dyn std::error::Error + Sync + Send>> {
+                let bytes = raw.get(i * 2..i * 2 + 2).ok_or("truncated NUMERIC")?;
+                Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+            };
+        let count = word(0)? as usize;
+        let weight = word(1)? as i16 as isize;
+        let sign = word(2)?;
+        let scale = word(3)? as usize;
+        let digits = (0..count)
+            .map(|i| word(4 + i))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let digit = |i: isize| {
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| digits.get(i).copied())
+                .unwrap_or(0)
+        };
+
+        let mut text = match sign {
+            0x0000 => String::new(),
+            0x4000 => "-".to_string(),
+            0xC000 => return Ok(Numeric("NaN".to_string())),
+            0xD000 => return Ok(Numeric("Infinity".to_string())),
+            0xF000 => return Ok(Numeric("-Infinity".to_string())),
+            _ => return Err("invalid NUMERIC sign".into()),
+        };
+        if weight < 0 {
+            text.push('0');
+        }
+        for i in 0..=weight {
+            if i == 0 {
+                text.push_str(&digit(i).to_string());
+            } else {
+                text.push_str(&format!("{:04}", digit(i)));
             }
         }
+        if scale > 0 {
+            let mut fraction = String::new();
+            let mut i = weight + 1;
+            while fraction.len() < scale {
+                fraction.push_str(&format!("{:04}", digit(i)));
+                i += 1;
+            }
+            fraction.truncate(scale);
+            text.push('.');
+            text.push_str(&fraction);
+        }
+        Ok(Numeric(text))
+    }
 
-        // Fallback in case macro doesn't return
-        format!("[{} UNSUPPORTED]", col_type.name())
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::NUMERIC
     }
 }
 
@@ -561,10 +1831,96 @@ impl DatabaseConnection for PostgresDatabase {
             tables,
             views,
             indexes,
+            materialized_views: self.load_materialized_views()?,
+            sequences: self.load_sequences()?,
+            functions: self.load_functions()?,
+            types: self.load_types()?,
         })
     }
 
-    fn execute_query(&self, sql: &str) -> QueryResult {
+    fn definition(&self, kind: ObjectKind, schema: Option<&str>, name: &str) -> Result<String> {
+        let schema = schema.unwrap_or("public");
+        let qualified = qualified_name(Some(schema), name);
+        let mut client = self.client.borrow_mut();
+        let definition = match kind {
+            ObjectKind::Table => Self::table_definition(&mut client, schema, name)?,
+            ObjectKind::View | ObjectKind::MaterializedView => {
+                let query = "SELECT pg_get_viewdef(c.oid, true) FROM pg_class c \
+                             JOIN pg_namespace n ON n.oid = c.relnamespace \
+                             WHERE n.nspname = $1 AND c.relname = $2";
+                let body: String = client.query_one(query, &[&schema, &name])?.get(0);
+                let create = if kind == ObjectKind::View {
+                    "CREATE OR REPLACE VIEW"
+                } else {
+                    "CREATE MATERIALIZED VIEW"
+                };
+                // The view body comes back ending in a semicolon
+                format!("{} {} AS\n{}", create, qualified, body.trim_start())
+            }
+            ObjectKind::Sequence => {
+                let query = "SELECT data_type::text, increment_by, min_value, max_value, \
+                                start_value, cache_size, cycle \
+                             FROM pg_sequences WHERE schemaname = $1 AND sequencename = $2";
+                let row = client.query_one(query, &[&schema, &name])?;
+                let cycle: bool = row.get(6);
+                format!(
+                    "CREATE SEQUENCE {} AS {}\n    INCREMENT BY {}\n    MINVALUE {}\n    MAXVALUE {}\n    START WITH {}\n    CACHE {}\n    {};",
+                    qualified,
+                    row.get::<_, String>(0),
+                    row.get::<_, i64>(1),
+                    row.get::<_, i64>(2),
+                    row.get::<_, i64>(3),
+                    row.get::<_, i64>(4),
+                    row.get::<_, i64>(5),
+                    if cycle { "CYCLE" } else { "NO CYCLE" }
+                )
+            }
+            ObjectKind::Function => {
+                let query = "SELECT p.oid, p.prokind = 'a' FROM pg_proc p \
+                             JOIN pg_namespace n ON n.oid = p.pronamespace \
+                             WHERE n.nspname = $1 \
+                               AND p.proname || '(' || pg_get_function_identity_arguments(p.oid) || ')' = $2";
+                let row = client.query_one(query, &[&schema, &name])?;
+                let (oid, is_aggregate): (u32, bool) = (row.get(0), row.get(1));
+                if is_aggregate {
+                    Self::aggregate_definition(&mut client, schema, oid)?
+                } else {
+                    let body: String = client
+                        .query_one("SELECT pg_get_functiondef($1)", &[&oid])?
+                        .get(0);
+                    format!("{};", body.trim_end())
+                }
+            }
+            ObjectKind::Type => {
+                let query = "SELECT t.typtype = 'e', \
+                                ARRAY(SELECT quote_literal(e.enumlabel) FROM pg_enum e \
+                                      WHERE e.enumtypid = t.oid ORDER BY e.enumsortorder), \
+                                ARRAY(SELECT quote_ident(a.attname) || ' ' || format_type(a.atttypid, a.atttypmod) \
+                                      FROM pg_attribute a \
+                                      WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped \
+                                      ORDER BY a.attnum) \
+                             FROM pg_type t JOIN pg_namespace n ON n.oid = t.typnamespace \
+                             WHERE n.nspname = $1 AND t.typname = $2";
+                let row = client.query_one(query, &[&schema, &name])?;
+                let is_enum: bool = row.get(0);
+                let (form, members): (&str, Vec<String>) = if is_enum {
+                    ("AS ENUM", row.get(1))
+                } else {
+                    ("AS", row.get(2))
+                };
+                format!(
+                    "CREATE TYPE {} {} (\n    {}\n);",
+                    qualified,
+                    form,
+                    members.join(",\n    ")
+                )
+            }
+        };
+
+        Ok(definition)
+    }
+
+    fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult {
         let start = Instant::now();
 
         let trimmed = sql.trim();
@@ -572,7 +1928,7 @@ impl DatabaseConnection for PostgresDatabase {
             return QueryResult::error("Empty query".to_string(), start.elapsed());
         }
 
-        let result = self.execute_query_inner(trimmed);
+        let result = self.stream_query_inner(trimmed, first_batch, sink);
         let elapsed = start.elapsed();
 
         match result {
@@ -580,58 +1936,1437 @@ impl DatabaseConnection for PostgresDatabase {
                 qr.execution_time = elapsed;
                 qr
             }
-            Err(e) => QueryResult::error(e.to_string(), elapsed),
+            Err(e) => {
+                // Postgres refuses everything but ROLLBACK after an error in a transaction
+                if self.transaction.get() == TransactionState::Open {
+                    self.transaction.set(TransactionState::Failed);
+                }
+                // Show the server's message rather than the driver's bare "db error"
+                let message = match e
+                    .downcast_ref::<postgres::Error>()
+                    .and_then(postgres::Error::as_db_error)
+                {
+                    Some(db_error) => db_error.to_string(),
+                    None => format!("{:#}", e),
+                };
+                QueryResult::error(message, elapsed)
+            }
+        }
+    }
+
+    fn transaction_state(&self) -> TransactionState {
+        self.transaction.get()
+    }
+
+    fn begin_transaction(&self) -> Result<()> {
+        self.client.borrow_mut().batch_execute("BEGIN")?;
+        self.transaction.set(TransactionState::Open);
+        Ok(())
+    }
+
+    fn end_transaction(&self, commit: bool) -> Result<()> {
+        let sql = if commit { "COMMIT" } else { "ROLLBACK" };
+        // The transaction is over even if COMMIT fails; the server rolls it back
+        let result = self.client.borrow_mut().batch_execute(sql);
+        self.transaction.set(TransactionState::Idle);
+        Ok(result?)
+    }
+
+    fn cancel_handle(&self) -> CancelHandle {
+        // Cancelling opens a new connection to the server
+        let (token, tls) = (self.cancel_token.clone(), self.tls.clone());
+        CancelHandle::new(move || {
+            let _ = token.cancel_query(tls.clone());
+        })
+    }
+
+    fn dialect(&self) -> Dialect {
+        Dialect {
+            name: "PostgreSQL",
+            savepoints: true,
+            backslash_escapes: false,
+            bytea_literals: true,
+            identifier_quote: '"',
         }
     }
 
     fn get_display_name(&self) -> String {
         self.connection_string.clone()
     }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn is_closed(&self) -> bool {
+        self.client.borrow().is_closed()
+    }
+}
+
+/// Connects to PostgreSQL by URL or by a `key=value` string such as `service=app`.
+pub struct PostgresBackend;
+
+impl Backend for PostgresBackend {
+    fn schemes(&self) -> &[&str] {
+        &["postgres", "postgresql"]
+    }
+
+    fn accepts(&self, connection_string: &str) -> bool {
+        is_key_value(connection_string)
+    }
+
+    fn connect(
+        &self,
+        connection_string: &str,
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>> {
+        Ok(Box::new(PostgresDatabase::open(
+            connection_string,
+            read_only,
+        )?))
+    }
 }
 
-// Public enum wrapper
-pub enum Database {
-    Sqlite(SqliteDatabase),
-    Postgres(PostgresDatabase),
+// MySQL/MariaDB implementation
+pub struct MysqlDatabase {
+    conn: RefCell<mysql::Conn>,
+    opts: mysql::Opts,
+    connection_string: String,
+    transaction: Cell<TransactionState>,
+    read_only: bool,
 }
 
-impl Database {
-    pub fn open(connection_string: &str) -> Result<Self> {
-        // Auto-detect database type
-        if connection_string.starts_with("postgres://")
-            || connection_string.starts_with("postgresql://")
-            || connection_string.contains("host=")
-        {
-            Ok(Database::Postgres(PostgresDatabase::open(
-                connection_string,
-            )?))
+impl MysqlDatabase {
+    fn open(connection_string: &str, read_only: bool) -> Result<Self> {
+        // The driver only knows the mysql scheme
+        let url = match connection_string.strip_prefix("mariadb://") {
+            Some(rest) => format!("mysql://{}", rest),
+            None => connection_string.to_string(),
+        };
+        let opts = mysql::Opts::from_url(&url).with_context(|| {
+            format!(
+                "Invalid connection string: {}",
+                redact_password(connection_string)
+            )
+        })?;
+
+        let mut init = vec![];
+        if read_only {
+            init.push("SET SESSION TRANSACTION READ ONLY");
+        }
+        let opts: mysql::Opts = mysql::OptsBuilder::from_opts(opts).init(init).into();
+        let conn = mysql::Conn::new(opts.clone()).with_context(|| {
+            format!(
+                "Failed to connect to MySQL: {}",
+                redact_password(connection_string)
+            )
+        })?;
+
+        Ok(Self {
+            conn: RefCell::new(conn),
+            opts,
+            connection_string: connection_string.to_string(),
+            transaction: Cell::new(TransactionState::Idle),
+            read_only,
+        })
+    }
+
+    /// Tables or views of the current database.
+    fn load_tables(&self, is_view: bool) -> Result<Vec<TableInfo>> {
+        let table_type = if is_view { "VIEW" } else { "BASE TABLE" };
+        let names: Vec<String> = self.conn.borrow_mut().exec(
+            "SELECT table_name FROM information_schema.tables \
+             WHERE table_schema = DATABASE() AND table_type = ? \
+             ORDER BY table_name",
+            (table_type,),
+        )?;
+
+        let mut columns = self.load_columns()?;
+        let (mut foreign_keys, mut constraints, mut triggers) = if is_view {
+            Default::default()
         } else {
-            Ok(Database::Sqlite(SqliteDatabase::open(connection_string)?))
+            (
+                self.load_foreign_keys()?,
+                self.load_constraints()?,
+                self.load_triggers()?,
+            )
+        };
+        let tables = names
+            .into_iter()
+            .map(|name| TableInfo {
+                schema: None,
+                columns: columns.remove(&name).unwrap_or_default(),
+                foreign_keys: foreign_keys.remove(&name).unwrap_or_default(),
+                constraints: constraints.remove(&name).unwrap_or_default(),
+                triggers: triggers.remove(&name).unwrap_or_default(),
+                name,
+            })
+            .collect();
+
+        Ok(tables)
+    }
+
+    /// Columns of every table and view, keyed by table name.
+    fn load_columns(&self) -> Result<HashMap<String, Vec<ColumnInfo>>> {
+        type ColumnRow = (
+            String,
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+            String,
+            Option<String>,
+        );
+        let rows: Vec<ColumnRow> = self.conn.borrow_mut().query(
+            "SELECT table_name, column_name, column_type, is_nullable, column_key, \
+                column_default, extra, generation_expression \
+             FROM information_schema.columns \
+             WHERE table_schema = DATABASE() \
+             ORDER BY table_name, ordinal_position",
+        )?;
+
+        let mut columns: HashMap<String, Vec<ColumnInfo>> = HashMap::new();
+        for (table, name, data_type, nullable, key, default, extra, expression) in rows {
+            let extra = extra.to_lowercase();
+            // extra reads e.g. "auto_increment" or "VIRTUAL GENERATED"
+            let generated = match expression.filter(|e| !e.is_empty()) {
+                Some(expression) => Some(format!("generated as ({})", expression)),
+                None if extra.contains("auto_increment") => Some("auto_increment".to_string()),
+                None => None,
+            };
+            columns.entry(table).or_default().push(ColumnInfo {
+                name,
+                data_type,
+                is_nullable: nullable == "YES",
+                is_primary_key: key == "PRI",
+                default,
+                generated,
+            });
         }
+
+        Ok(columns)
     }
 
-    pub fn load_schema(&self) -> Result<Schema> {
-        match self {
-            Database::Sqlite(db) => db.load_schema(),
-            Database::Postgres(db) => db.load_schema(),
+    /// Foreign keys of every table, keyed by table name.
+    fn load_foreign_keys(&self) -> Result<HashMap<String, Vec<ForeignKey>>> {
+        let rows: Vec<(String, String, String, String, String, String)> =
+            self.conn.borrow_mut().query(
+                "SELECT table_name, constraint_name, column_name, \
+                    referenced_table_schema, referenced_table_name, referenced_column_name \
+                 FROM information_schema.key_column_usage \
+                 WHERE table_schema = DATABASE() AND referenced_table_name IS NOT NULL \
+                 ORDER BY table_name, constraint_name, ordinal_position",
+            )?;
+        let database = self.current_database()?;
+
+        let mut foreign_keys: HashMap<String, Vec<ForeignKey>> = HashMap::new();
+        let mut current: Option<(String, String)> = None;
+        for (table, constraint, column, ref_schema, ref_table, ref_column) in rows {
+            let keys = foreign_keys.entry(table.clone()).or_default();
+            let key = (table, constraint);
+            if current.as_ref() != Some(&key) {
+                // Tables of the current database are named without their schema
+                keys.push(ForeignKey {
+                    columns: vec![],
+                    ref_schema: Some(ref_schema).filter(|s| Some(s) != database.as_ref()),
+                    ref_table,
+                    ref_columns: vec![],
+                });
+                current = Some(key);
+            }
+            if let Some(fk) = keys.last_mut() {
+                fk.columns.push(column);
+                fk.ref_columns.push(ref_column);
+            }
         }
+
+        Ok(foreign_keys)
     }
 
-    pub fn execute_query(&self, sql: &str) -> QueryResult {
-        match self {
-            Database::Sqlite(db) => db.execute_query(sql),
-            Database::Postgres(db) => db.execute_query(sql),
+    /// UNIQUE and CHECK constraints of every table, keyed by table name.
+    fn load_constraints(&self) -> Result<HashMap<String, Vec<Constraint>>> {
+        let mut conn = self.conn.borrow_mut();
+        let unique: Vec<(String, String, String)> = conn.query(
+            "SELECT tc.table_name, tc.constraint_name, \
+                GROUP_CONCAT(k.column_name ORDER BY k.ordinal_position SEPARATOR ', ') \
+             FROM information_schema.table_constraints tc \
+             JOIN information_schema.key_column_usage k \
+                ON k.constraint_schema = tc.constraint_schema \
+                AND k.constraint_name = tc.constraint_name \
+                AND k.table_name = tc.table_name \
+             WHERE tc.table_schema = DATABASE() AND tc.constraint_type = 'UNIQUE' \
+             GROUP BY tc.table_name, tc.constraint_name \
+             ORDER BY tc.constraint_name",
+        )?;
+        // Servers before MySQL 8.0.16 have no CHECK constraints to list
+        let checks: Vec<(String, String, String)> = conn
+            .query(
+                "SELECT tc.table_name, tc.constraint_name, cc.check_clause \
+                 FROM information_schema.table_constraints tc \
+                 JOIN information_schema.check_constraints cc \
+                    ON cc.constraint_schema = tc.constraint_schema \
+                    AND cc.constraint_name = tc.constraint_name \
+                 WHERE tc.table_schema = DATABASE() AND tc.constraint_type = 'CHECK' \
+                 ORDER BY tc.constraint_name",
+            )
+            .unwrap_or_default();
+
+        let mut constraints: HashMap<String, Vec<Constraint>> = HashMap::new();
+        let definitions = unique
+            .into_iter()
+            .map(|(table, name, columns)| (table, name, format!("UNIQUE ({})", columns)))
+            .chain(
+                checks
+                    .into_iter()
+                    .map(|(table, name, clause)| (table, name, format!("CHECK ({})", clause))),
+            );
+        for (table, name, definition) in definitions {
+            constraints.entry(table).or_default().push(Constraint {
+                name: Some(name),
+                definition,
+            });
         }
+
+        Ok(constraints)
     }
 
-    pub fn get_display_name(&self) -> String {
-        match self {
-            Database::Sqlite(db) => db.get_display_name(),
-            Database::Postgres(db) => db.get_display_name(),
+    /// Triggers of every table, keyed by table name.
+    fn load_triggers(&self) -> Result<HashMap<String, Vec<Trigger>>> {
+        let rows: Vec<(String, String, String, String)> = self.conn.borrow_mut().query(
+            "SELECT event_object_table, trigger_name, action_timing, event_manipulation \
+             FROM information_schema.triggers \
+             WHERE trigger_schema = DATABASE() \
+             ORDER BY trigger_name",
+        )?;
+
+        let mut triggers: HashMap<String, Vec<Trigger>> = HashMap::new();
+        for (table, name, timing, event) in rows {
+            triggers.entry(table).or_default().push(Trigger {
+                name,
+                timing: format!("{} {}", timing, event),
+            });
         }
+
+        Ok(triggers)
     }
 
-    pub fn path(&self) -> String {
-        self.get_display_name()
+    fn load_indexes(&self) -> Result<Vec<IndexInfo>> {
+        type KeyPart = (String, String, i64, String, Option<String>, Option<i64>);
+        // column_name is NULL for functional key parts
+        let rows: Vec<KeyPart> = self.conn.borrow_mut().query(
+            "SELECT table_name, index_name, non_unique, index_type, column_name, sub_part \
+             FROM information_schema.statistics \
+             WHERE table_schema = DATABASE() \
+             ORDER BY index_name, table_name, seq_in_index",
+        )?;
+
+        let mut indexes: Vec<IndexInfo> = Vec::new();
+        for (table, name, non_unique, method, column, prefix) in rows {
+            let column = match (column, prefix) {
+                (Some(column), Some(length)) => format!("{}({})", column, length),
+                (Some(column), None) => column,
+                (None, _) => "(expression)".to_string(),
+            };
+            match indexes.last_mut() {
+                Some(index) if index.name == name && index.table_name == table => {
+                    index.columns.push(column)
+                }
+                _ => indexes.push(IndexInfo {
+                    schema: None,
+                    name,
+                    table_name: table,
+                    is_unique: non_unique == 0,
+                    columns: vec![column],
+                    method: method.to_lowercase(),
+                    predicate: None,
+                    size: None,
+                    scans: None,
+                }),
+            }
+        }
+
+        Ok(indexes)
+    }
+
+    fn current_database(&self) -> Result<Option<String>> {
+        let database: Option<Option<String>> =
+            self.conn.borrow_mut().query_first("SELECT DATABASE()")?;
+        Ok(database.flatten())
+    }
+
+    fn stream_query_inner(
+        &self,
+        sql: &str,
+        first_batch: usize,
+        sink: &mut RowSink,
+    ) -> Result<QueryResult> {
+        let mut conn = self.conn.borrow_mut();
+        let connection_id = conn.connection_id();
+        let mut query_result = conn.query_iter(sql)?;
+        let columns: Vec<ResultColumn> = query_result
+            .columns()
+            .as_ref()
+            .iter()
+            .map(|column| ResultColumn {
+                name: column.name_str().to_string(),
+                data_type: Self::type_name(column).to_string(),
+            })
+            .collect();
+
+        let mut result = QueryResult::empty(columns.clone());
+        if columns.is_empty() {
+            if dml_verb(sql).is_some() {
+                result.rows_affected = Some(query_result.affected_rows());
+                result.last_insert_rowid = query_result
+                    .last_insert_id()
+                    .filter(|&id| id > 0)
+                    .and_then(|id| i64::try_from(id).ok());
+            }
+            drop(query_result);
+            self.track_transaction_control(sql);
+            return Ok(result);
+        }
+
+        let Some(mut rows) = query_result.iter() else {
+            return Ok(result);
+        };
+        let mut wanted = first_batch;
+        let mut batch = Vec::new();
+        loop {
+            if wanted == 0 {
+                result.has_more = rows.next().transpose()?.is_some();
+                // The protocol can't stop a result set midway and dropping it reads the
+                // rest, so the query is killed first
+                if result.has_more {
+                    Self::kill_query(&self.opts, connection_id);
+                }
+                break;
+            }
+            let Some(row) = rows.next().transpose()? else {
+                if !batch.is_empty() {
+                    sink(&columns, batch);
+                }
+                break;
+            };
+            let row_data = row
+                .columns_ref()
+                .iter()
+                .enumerate()
+                .map(|(i, column)| Self::get_column_value(row.as_ref(i), column))
+                .collect();
+            batch.push(row_data);
+            if batch.len() >= wanted {
+                wanted = sink(&columns, std::mem::take(&mut batch));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Stops the statement running on connection `connection_id`, from a second
+    /// connection.
+    fn kill_query(opts: &mysql::Opts, connection_id: u32) {
+        if let Ok(mut conn) = mysql::Conn::new(opts.clone()) {
+            let _ = conn.query_drop(format!("KILL QUERY {}", connection_id));
+        }
+    }
+
+    /// Follows statements that start or end a transaction, including those MySQL
+    /// commits implicitly, so later statements know whether they run inside one.
+    fn track_transaction_control(&self, sql: &str) {
+        let state = match first_keyword(sql).as_str() {
+            "BEGIN" | "START" => TransactionState::Open,
+            "ROLLBACK" if sql.to_uppercase().contains(" TO ") => return,
+            "COMMIT" | "ROLLBACK" | "CREATE" | "ALTER" | "DROP" | "RENAME" | "TRUNCATE"
+            | "LOCK" | "UNLOCK" => TransactionState::Idle,
+            _ => return,
+        };
+        self.transaction.set(state);
+    }
+
+    fn type_name(column: &mysql::Column) -> &'static str {
+        use mysql::consts::ColumnType::*;
+
+        let binary = column.character_set() == BINARY_CHARSET;
+        match column.column_type() {
+            MYSQL_TYPE_TINY => "tinyint",
+            MYSQL_TYPE_SHORT => "smallint",
+            MYSQL_TYPE_INT24 => "mediumint",
+            MYSQL_TYPE_LONG => "int",
+            MYSQL_TYPE_LONGLONG => "bigint",
+            MYSQL_TYPE_YEAR => "year",
+            MYSQL_TYPE_FLOAT => "float",
+            MYSQL_TYPE_DOUBLE => "double",
+            MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => "decimal",
+            MYSQL_TYPE_BIT => "bit",
+            MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => "date",
+            MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => "time",
+            MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2 => "datetime",
+            MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2 => "timestamp",
+            MYSQL_TYPE_JSON => "json",
+            MYSQL_TYPE_ENUM => "enum",
+            MYSQL_TYPE_SET => "set",
+            MYSQL_TYPE_GEOMETRY => "geometry",
+            MYSQL_TYPE_TINY_BLOB
+            | MYSQL_TYPE_BLOB
+            | MYSQL_TYPE_MEDIUM_BLOB
+            | MYSQL_TYPE_LONG_BLOB => {
+                if binary {
+                    "blob"
+                } else {
+                    "text"
+                }
+            }
+            MYSQL_TYPE_STRING if binary => "binary",
+            MYSQL_TYPE_STRING => "char",
+            MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING if binary => "varbinary",
+            MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => "varchar",
+            _ => "",
+        }
+    }
+
+    fn get_column_value(value: Option<&mysql::Value>, column: &mysql::Column) -> CellValue {
+        use mysql::consts::ColumnType::*;
+
+        // The text protocol sends every value as its text form
+        let bytes = match value {
+            None | Some(mysql::Value::NULL) => return CellValue::Null,
+            Some(mysql::Value::Bytes(bytes)) => bytes,
+            Some(other) => return CellValue::Text(other.as_sql(true)),
+        };
+        let text = String::from_utf8_lossy(bytes).into_owned();
+        let parsed = match column.column_type() {
+            // BOOLEAN is an alias for TINYINT(1)
+            MYSQL_TYPE_TINY if column.column_length() == 1 => Some(CellValue::Bool(text != "0")),
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+            | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR => text.parse().ok().map(CellValue::Int),
+            MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => text.parse().ok().map(CellValue::Float),
+            MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL => Some(CellValue::Decimal(text.clone())),
+            MYSQL_TYPE_JSON => serde_json::from_str(&text).ok().map(CellValue::Json),
+            // Zero dates such as 0000-00-00 stay text
+            MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE => {
+                chrono::NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                    .ok()
+                    .map(CellValue::Date)
+            }
+            MYSQL_TYPE_DATETIME
+            | MYSQL_TYPE_DATETIME2
+            | MYSQL_TYPE_TIMESTAMP
+            | MYSQL_TYPE_TIMESTAMP2 => {
+                chrono::NaiveDateTime::parse_from_str(&text, "%Y-%m-%d %H:%M:%S%.f")
+                    .ok()
+                    .map(CellValue::Timestamp)
+            }
+            // TIME also holds durations beyond a day, which stay text
+            MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => {
+                chrono::NaiveTime::parse_from_str(&text, "%H:%M:%S%.f")
+                    .ok()
+                    .map(CellValue::Time)
+            }
+            MYSQL_TYPE_BIT | MYSQL_TYPE_GEOMETRY => Some(CellValue::Bytes(bytes.clone())),
+            _ if column.character_set() == BINARY_CHARSET => Some(CellValue::Bytes(bytes.clone())),
+            _ => None,
+        };
+        parsed.unwrap_or(CellValue::Text(text))
+    }
+}
+
+/// Character set number MySQL reports for binary strings.
+const BINARY_CHARSET: u16 = 63;
+
+impl DatabaseConnection for MysqlDatabase {
+    fn load_schema(&self) -> Result<Schema> {
+        let tables = self.load_tables(false)?;
+        let views = self.load_tables(true)?;
+        let indexes = self.load_indexes()?;
+
+        Ok(Schema {
+            tables,
+            views,
+            indexes,
+            ..Default::default()
+        })
+    }
+
+    fn definition(&self, kind: ObjectKind, _schema: Option<&str>, name: &str) -> Result<String> {
+        let (object, column) = match kind {
+            ObjectKind::Table => ("TABLE", 1),
+            ObjectKind::View => ("VIEW", 1),
+            _ => anyhow::bail!("MySQL has no {:?} objects", kind),
+        };
+        let mut conn = self.conn.borrow_mut();
+        let row: Option<mysql::Row> = conn.query_first(format!(
+            "SHOW CREATE {} {}",
+            object,
+            self.dialect().quote_identifier(name)
+        ))?;
+        let definition: Option<String> = row.and_then(|row| row.get(column));
+        let definition = definition.with_context(|| format!("{} not found", name))?;
+        Ok(format!("{};", definition))
+    }
+
+    fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult {
+        let start = Instant::now();
+
+        let trimmed = sql.trim();
+        if trimmed.is_empty() {
+            return QueryResult::error("Empty query".to_string(), start.elapsed());
+        }
+
+        let result = self.stream_query_inner(trimmed, first_batch, sink);
+        let elapsed = start.elapsed();
+
+        match result {
+            Ok(mut qr) => {
+                qr.execution_time = elapsed;
+                qr
+            }
+            Err(e) => QueryResult::error(e.to_string(), elapsed),
+        }
+    }
+
+    fn transaction_state(&self) -> TransactionState {
+        // Like SQLite, MySQL keeps a transaction usable after a failed statement
+        self.transaction.get()
+    }
+
+    fn begin_transaction(&self) -> Result<()> {
+        self.conn.borrow_mut().query_drop("BEGIN")?;
+        self.transaction.set(TransactionState::Open);
+        Ok(())
+    }
+
+    fn end_transaction(&self, commit: bool) -> Result<()> {
+        let sql = if commit { "COMMIT" } else { "ROLLBACK" };
+        let result = self.conn.borrow_mut().query_drop(sql);
+        self.transaction.set(TransactionState::Idle);
+        Ok(result?)
+    }
+
+    fn cancel_handle(&self) -> CancelHandle {
+        // Cancelling kills the query from a second connection
+        let opts = self.opts.clone();
+        let connection_id = self.conn.borrow().connection_id();
+        CancelHandle::new(move || Self::kill_query(&opts, connection_id))
+    }
+
+    fn dialect(&self) -> Dialect {
+        Dialect {
+            name: "MySQL",
+            savepoints: true,
+            backslash_escapes: true,
+            bytea_literals: false,
+            identifier_quote: '`',
+        }
+    }
+
+    fn get_display_name(&self) -> String {
+        self.connection_string.clone()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+/// Connects to MySQL or MariaDB by URL.
+pub struct MysqlBackend;
+
+impl Backend for MysqlBackend {
+    fn schemes(&self) -> &[&str] {
+        &["mysql", "mariadb"]
+    }
+
+    fn connect(
+        &self,
+        connection_string: &str,
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>> {
+        Ok(Box::new(MysqlDatabase::open(connection_string, read_only)?))
+    }
+}
+
+// DuckDB implementation
+#[cfg(feature = "duckdb")]
+pub struct DuckdbDatabase {
+    conn: duckdb::Connection,
+    interrupt: Arc<duckdb::InterruptHandle>,
+    path: String,
+    transaction: Cell<TransactionState>,
+    read_only: bool,
+}
+
+#[cfg(feature = "duckdb")]
+impl DuckdbDatabase {
+    /// Opens a `.duckdb` file, or an in-memory database for `duckdb:` or `duckdb::memory:`.
+    fn open(connection_string: &str, read_only: bool) -> Result<Self> {
+        let path = connection_string
+            .strip_prefix("duckdb:")
+            .unwrap_or(connection_string);
+        let conn = if path.is_empty() || path == ":memory:" {
+            if read_only {
+                anyhow::bail!("An in-memory DuckDB database can't be opened read-only");
+            }
+            duckdb::Connection::open_in_memory()?
+        } else if read_only {
+            if !Path::new(path).exists() {
+                anyhow::bail!("Database file not found: {}", path);
+            }
+            let config = duckdb::Config::default().access_mode(duckdb::AccessMode::ReadOnly)?;
+            duckdb::Connection::open_with_flags(path, config)
+                .with_context(|| format!("Failed to open database: {}", path))?
+        } else {
+            duckdb::Connection::open(path)
+                .with_context(|| format!("Failed to open database: {}", path))?
+        };
+        let interrupt = conn.interrupt_handle();
+
+        Ok(Self {
+            conn,
+            interrupt,
+            path: connection_string.to_string(),
+            transaction: Cell::new(TransactionState::Idle),
+            read_only,
+        })
+    }
+
+    /// Tables or views of the current schema.
+    fn load_tables(&self, is_view: bool) -> Result<Vec<TableInfo>> {
+        let catalog = if is_view {
+            "duckdb_views()"
+        } else {
+            "duckdb_tables()"
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}_name FROM {} \
+             WHERE database_name = current_database() AND schema_name = current_schema() \
+                AND NOT internal \
+             ORDER BY 1",
+            if is_view { "view" } else { "table" },
+            catalog
+        ))?;
+        let names: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<duckdb::Result<_>>()?;
+
+        let mut columns = self.load_columns()?;
+        let (mut foreign_keys, mut constraints) = if is_view {
+            Default::default()
+        } else {
+            (self.load_foreign_keys()?, self.load_constraints()?)
+        };
+        let tables = names
+            .into_iter()
+            .map(|name| TableInfo {
+                schema: None,
+                columns: columns.remove(&name).unwrap_or_default(),
+                foreign_keys: foreign_keys.remove(&name).unwrap_or_default(),
+                constraints: constraints.remove(&name).unwrap_or_default(),
+                // DuckDB has no triggers
+                triggers: vec![],
+                name,
+            })
+            .collect();
+
+        Ok(tables)
+    }
+
+    /// Columns of every table and view, keyed by table name.
+    fn load_columns(&self) -> Result<HashMap<String, Vec<ColumnInfo>>> {
+        let primary_keys: HashSet<(String, String)> = self
+            .constraint_rows("PRIMARY KEY")?
+            .into_iter()
+            .flat_map(|(table, _, _, columns, _, _)| {
+                columns
+                    .into_iter()
+                    .map(move |column| (table.clone(), column))
+            })
+            .collect();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT table_name, column_name, data_type, is_nullable, column_default \
+             FROM duckdb_columns() \
+             WHERE database_name = current_database() AND schema_name = current_schema() \
+             ORDER BY table_name, column_index",
+        )?;
+        let mut rows = stmt.query([])?;
+
+        let mut columns: HashMap<String, Vec<ColumnInfo>> = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let table: String = row.get(0)?;
+            let name: String = row.get(1)?;
+            columns.entry(table.clone()).or_default().push(ColumnInfo {
+                is_primary_key: primary_keys.contains(&(table, name.clone())),
+                name,
+                data_type: row.get(2)?,
+                is_nullable: row.get(3)?,
+                default: row.get(4)?,
+                generated: None,
+            });
+        }
+
+        Ok(columns)
+    }
+
+    /// Constraints of one type in the current schema, as (table, name, text, columns,
+    /// referenced table, referenced columns).
+    fn constraint_rows(&self, constraint_type: &str) -> Result<Vec<ConstraintRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT table_name, constraint_name, constraint_text, constraint_column_names, \
+                referenced_table, referenced_column_names \
+             FROM duckdb_constraints() \
+             WHERE database_name = current_database() AND schema_name = current_schema() \
+                AND constraint_type = ? \
+             ORDER BY table_name, constraint_index",
+        )?;
+        let rows = stmt
+            .query_map([constraint_type], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    text_list(row.get(3)?),
+                    row.get(4)?,
+                    text_list(row.get(5)?),
+                ))
+            })?
+            .collect::<duckdb::Result<_>>()?;
+        Ok(rows)
+    }
+
+    /// Foreign keys of every table, keyed by table name.
+    fn load_foreign_keys(&self) -> Result<HashMap<String, Vec<ForeignKey>>> {
+        let mut foreign_keys: HashMap<String, Vec<ForeignKey>> = HashMap::new();
+        for (table, _, _, columns, ref_table, ref_columns) in self.constraint_rows("FOREIGN KEY")? {
+            let Some(ref_table) = ref_table else {
+                continue;
+            };
+            foreign_keys.entry(table).or_default().push(ForeignKey {
+                columns,
+                ref_schema: None,
+                ref_table,
+                ref_columns,
+            });
+        }
+        Ok(foreign_keys)
+    }
+
+    /// UNIQUE and CHECK constraints of every table, keyed by table name.
+    fn load_constraints(&self) -> Result<HashMap<String, Vec<Constraint>>> {
+        let rows = self
+            .constraint_rows("UNIQUE")?
+            .into_iter()
+            .chain(self.constraint_rows("CHECK")?);
+
+        let mut constraints: HashMap<String, Vec<Constraint>> = HashMap::new();
+        for (table, name, definition, ..) in rows {
+            constraints
+                .entry(table)
+                .or_default()
+                .push(Constraint { name, definition });
+        }
+        Ok(constraints)
+    }
+
+    fn load_indexes(&self) -> Result<Vec<IndexInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT index_name, table_name, sql FROM duckdb_indexes() \
+             WHERE database_name = current_database() AND schema_name = current_schema() \
+             ORDER BY index_name",
+        )?;
+        let indexes = stmt
+            .query_map([], |row| {
+                let sql: Option<String> = row.get(2)?;
+                let definition = parse_create_index(sql.as_deref().unwrap_or_default());
+                Ok(IndexInfo {
+                    schema: None,
+                    name: row.get(0)?,
+                    table_name: row.get(1)?,
+                    is_unique: definition.is_unique,
+                    columns: definition.columns,
+                    method: "art".to_string(),
+                    predicate: definition.predicate,
+                    size: None,
+                    scans: None,
+                })
+            })?
+            .collect::<duckdb::Result<_>>()?;
+
+        Ok(indexes)
+    }
+
+    fn stream_query_inner(
+        &self,
+        sql: &str,
+        first_batch: usize,
+        sink: &mut RowSink,
+    ) -> Result<QueryResult> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let mut query_rows = stmt.query([])?;
+        self.track_transaction_control(sql);
+        // Column details are only known once the statement has run
+        let columns: Vec<ResultColumn> = match query_rows.as_ref() {
+            Some(stmt) => (0..stmt.column_count())
+                .map(|i| {
+                    let logical_type = stmt.column_logical_type(i);
+                    ResultColumn {
+                        name: stmt.column_name(i).cloned().unwrap_or_default(),
+                        data_type: logical_type
+                            .get_alias()
+                            .unwrap_or_else(|| format!("{:?}", logical_type.id()).to_lowercase()),
+                    }
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        // INSERT, UPDATE and DELETE without RETURNING report their changes as a Count row
+        if dml_verb(sql).is_some() && columns.len() == 1 && columns[0].name == "Count" {
+            let changes = match query_rows.next()? {
+                Some(row) => row.get::<_, i64>(0)?,
+                None => 0,
+            };
+            let mut result = QueryResult::empty(vec![]);
+            result.rows_affected = u64::try_from(changes).ok();
+            return Ok(result);
+        }
+
+        let mut wanted = first_batch;
+        let mut batch = Vec::new();
+        let mut has_more = false;
+        loop {
+            if wanted == 0 {
+                has_more = query_rows.next()?.is_some();
+                break;
+            }
+            let Some(row) = query_rows.next()? else {
+                if !batch.is_empty() {
+                    sink(&columns, batch);
+                }
+                break;
+            };
+            let row_data = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| match row.get::<_, duckdb::types::Value>(i) {
+                    Ok(value) => Self::get_column_value(value, &column.data_type),
+                    Err(_) => CellValue::Unsupported(column.data_type.clone()),
+                })
+                .collect();
+            batch.push(row_data);
+            if batch.len() >= wanted {
+                wanted = sink(&columns, std::mem::take(&mut batch));
+            }
+        }
+
+        let mut result = QueryResult::empty(columns);
+        result.has_more = has_more;
+        Ok(result)
+    }
+
+    /// Follows BEGIN/COMMIT/ROLLBACK typed into the editor, so later statements know
+    /// whether they run inside a transaction.
+    fn track_transaction_control(&self, sql: &str) {
+        let state = match first_keyword(sql).as_str() {
+            "BEGIN" | "START" => TransactionState::Open,
+            "COMMIT" | "END" | "ROLLBACK" | "ABORT" => TransactionState::Idle,
+            _ => return,
+        };
+        self.transaction.set(state);
+    }
+
+    fn get_column_value(value: duckdb::types::Value, data_type: &str) -> CellValue {
+        use duckdb::types::Value;
+
+        let int =
+            |i: Option<i64>, text: String| i.map(CellValue::Int).unwrap_or(CellValue::Text(text));
+        match value {
+            Value::Null => CellValue::Null,
+            Value::Boolean(b) => CellValue::Bool(b),
+            Value::TinyInt(i) => CellValue::Int(i.into()),
+            Value::SmallInt(i) => CellValue::Int(i.into()),
+            Value::Int(i) => CellValue::Int(i.into()),
+            Value::BigInt(i) => CellValue::Int(i),
+            Value::UTinyInt(i) => CellValue::Int(i.into()),
+            Value::USmallInt(i) => CellValue::Int(i.into()),
+            Value::UInt(i) => CellValue::Int(i.into()),
+            // Wider integers only become text when they don't fit
+            Value::UBigInt(i) => int(i64::try_from(i).ok(), i.to_string()),
+            Value::HugeInt(i) => int(i64::try_from(i).ok(), i.to_string()),
+            Value::UHugeInt(i) => int(i64::try_from(i).ok(), i.to_string()),
+            Value::Float(f) => CellValue::Float(f.into()),
+            Value::Double(f) => CellValue::Float(f),
+            Value::Decimal(d) => CellValue::Decimal(d.to_string()),
+            Value::Text(text) if data_type.eq_ignore_ascii_case("json") => {
+                serde_json::from_str(&text)
+                    .map(CellValue::Json)
+                    .unwrap_or(CellValue::Text(text))
+            }
+            Value::Text(text) | Value::Enum(text) => CellValue::Text(text),
+            Value::Blob(bytes) | Value::Geometry(bytes) => CellValue::Bytes(bytes),
+            Value::Date32(days) => chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+                .and_then(|epoch| epoch.checked_add_signed(chrono::TimeDelta::days(days.into())))
+                .map(CellValue::Date)
+                .unwrap_or(CellValue::Unsupported(data_type.to_string())),
+            Value::Time64(unit, time) => {
+                let micros = unit.to_micros(time);
+                chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+                    (micros / 1_000_000) as u32,
+                    (micros % 1_000_000 * 1000) as u32,
+                )
+                .map(CellValue::Time)
+                .unwrap_or(CellValue::Unsupported(data_type.to_string()))
+            }
+            // TIMESTAMPTZ values arrive in UTC
+            Value::Timestamp(unit, time) => {
+                match chrono::DateTime::from_timestamp_micros(unit.to_micros(time)) {
+                    Some(ts) if data_type == "timestamptz" => CellValue::TimestampTz(ts),
+                    Some(ts) => CellValue::Timestamp(ts.naive_utc()),
+                    None => CellValue::Unsupported(data_type.to_string()),
+                }
+            }
+            Value::Interval {
+                months,
+                days,
+                nanos,
+            } => CellValue::Text(format_interval(months, days, nanos)),
+            // LIST, STRUCT, MAP and friends open in the JSON viewer
+            nested => CellValue::Json(Self::json_value(&nested)),
+        }
+    }
+
+    fn json_value(value: &duckdb::types::Value) -> serde_json::Value {
+        use duckdb::types::Value;
+
+        match value {
+            Value::List(items) | Value::Array(items) => {
+                items.iter().map(Self::json_value).collect()
+            }
+            Value::Struct(fields) => fields
+                .iter()
+                .map(|(name, value)| (name.clone(), Self::json_value(value)))
+                .collect(),
+            // Maps with text keys read as objects, others as a list of entries
+            Value::Map(entries) if entries.keys().all(|k| matches!(k, Value::Text(_))) => entries
+                .iter()
+                .map(|(key, value)| match key {
+                    Value::Text(key) => (key.clone(), Self::json_value(value)),
+                    _ => unreachable!(),
+                })
+                .collect(),
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| {
+                    serde_json::json!({ "key": Self::json_value(key), "value": Self::json_value(value) })
+                })
+                .collect(),
+            Value::Union(value) => Self::json_value(value),
+            Value::Text(text) | Value::Enum(text) => serde_json::Value::String(text.clone()),
+            scalar => Self::get_column_value(scalar.clone(), "").to_json(),
+        }
+    }
+}
+
+/// Table, constraint name, constraint text, columns, referenced table and columns.
+#[cfg(feature = "duckdb")]
+type ConstraintRow = (
+    String,
+    Option<String>,
+    String,
+    Vec<String>,
+    Option<String>,
+    Vec<String>,
+);
+
+/// Writes an interval the way DuckDB prints it, e.g. `1 month 2 days 03:00:00`.
+#[cfg(feature = "duckdb")]
+fn format_interval(months: i32, days: i32, nanos: i64) -> String {
+    let count =
+        |n: i32, unit: &str| format!("{} {}{}", n, unit, if n.abs() == 1 { "" } else { "s" });
+    let mut parts = Vec::new();
+    for (n, unit) in [(months / 12, "year"), (months % 12, "month"), (days, "day")] {
+        if n != 0 {
+            parts.push(count(n, unit));
+        }
+    }
+    if nanos != 0 || parts.is_empty() {
+        let sign = if nanos < 0 { "-" } else { "" };
+        let micros = (nanos / 1000).unsigned_abs();
+        let secs = micros / 1_000_000;
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        if !micros.is_multiple_of(1_000_000) {
+            time.push_str(&format!(".{:06}", micros % 1_000_000));
+        }
+        parts.push(time);
+    }
+    parts.join(" ")
+}
+
+/// Reads a `VARCHAR[]` value, such as the column names of a constraint.
+#[cfg(feature = "duckdb")]
+fn text_list(value: duckdb::types::Value) -> Vec<String> {
+    match value {
+        duckdb::types::Value::List(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                duckdb::types::Value::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(feature = "duckdb")]
+impl DatabaseConnection for DuckdbDatabase {
+    fn load_schema(&self) -> Result<Schema> {
+        let tables = self.load_tables(false)?;
+        let views = self.load_tables(true)?;
+        let indexes = self.load_indexes()?;
+
+        Ok(Schema {
+            tables,
+            views,
+            indexes,
+            ..Default::default()
+        })
+    }
+
+    fn definition(&self, kind: ObjectKind, _schema: Option<&str>, name: &str) -> Result<String> {
+        let query = match kind {
+            ObjectKind::Table => "SELECT sql FROM duckdb_tables() WHERE table_name = ?",
+            ObjectKind::View => "SELECT sql FROM duckdb_views() WHERE view_name = ?",
+            _ => anyhow::bail!("DuckDB has no {:?} objects", kind),
+        };
+        let sql: String = self.conn.query_row(
+            &format!(
+                "{} AND database_name = current_database() AND schema_name = current_schema()",
+                query
+            ),
+            [name],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT sql FROM duckdb_indexes() \
+             WHERE table_name = ? AND database_name = current_database() \
+                AND schema_name = current_schema() AND sql IS NOT NULL \
+             ORDER BY index_name",
+        )?;
+        let indexes: Vec<String> = stmt
+            .query_map([name], |row| row.get(0))?
+            .collect::<duckdb::Result<_>>()?;
+
+        // DuckDB's SQL already ends with a semicolon
+        Ok(std::iter::once(sql)
+            .chain(indexes)
+            .map(|sql| format!("{};\n", sql.trim().trim_end_matches(';')))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult {
+        let start = Instant::now();
+
+        let trimmed = sql.trim();
+        if trimmed.is_empty() {
+            return QueryResult::error("Empty query".to_string(), start.elapsed());
+        }
+
+        let result = self.stream_query_inner(trimmed, first_batch, sink);
+        let elapsed = start.elapsed();
+
+        match result {
+            Ok(mut qr) => {
+                qr.execution_time = elapsed;
+                qr
+            }
+            Err(e) => {
+                // Like Postgres, DuckDB aborts the transaction a statement fails in
+                if self.transaction.get() == TransactionState::Open {
+                    self.transaction.set(TransactionState::Failed);
+                }
+                QueryResult::error(e.to_string(), elapsed)
+            }
+        }
+    }
+
+    fn transaction_state(&self) -> TransactionState {
+        self.transaction.get()
+    }
+
+    fn begin_transaction(&self) -> Result<()> {
+        self.conn.execute_batch("BEGIN")?;
+        self.transaction.set(TransactionState::Open);
+        Ok(())
+    }
+
+    fn end_transaction(&self, commit: bool) -> Result<()> {
+        let result = self
+            .conn
+            .execute_batch(if commit { "COMMIT" } else { "ROLLBACK" });
+        self.transaction.set(TransactionState::Idle);
+        Ok(result?)
+    }
+
+    fn cancel_handle(&self) -> CancelHandle {
+        let interrupt = Arc::clone(&self.interrupt);
+        CancelHandle::new(move || interrupt.interrupt())
+    }
+
+    fn dialect(&self) -> Dialect {
+        Dialect {
+            name: "DuckDB",
+            savepoints: false,
+            backslash_escapes: false,
+            bytea_literals: false,
+            identifier_quote: '"',
+        }
+    }
+
+    fn get_display_name(&self) -> String {
+        self.path.clone()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+/// Opens `.duckdb` files, or an in-memory database for `duckdb:`. Without the `duckdb`
+/// feature it only explains how to get DuckDB support.
+pub struct DuckdbBackend;
+
+impl Backend for DuckdbBackend {
+    fn schemes(&self) -> &[&str] {
+        &["duckdb"]
+    }
+
+    fn accepts(&self, connection_string: &str) -> bool {
+        connection_string.ends_with(".duckdb")
+    }
+
+    #[cfg(feature = "duckdb")]
+    fn connect(
+        &self,
+        connection_string: &str,
+        read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>> {
+        Ok(Box::new(DuckdbDatabase::open(
+            connection_string,
+            read_only,
+        )?))
+    }
+
+    #[cfg(not(feature = "duckdb"))]
+    fn connect(
+        &self,
+        connection_string: &str,
+        _read_only: bool,
+    ) -> Result<Box<dyn DatabaseConnection>> {
+        anyhow::bail!(
+            "{} is a DuckDB database; rebuild sqlclix with `--features duckdb` to open it",
+            connection_string
+        )
+    }
+}
+
+/// The scheme a connection string starts with, such as `postgres` in `postgres://...`.
+/// Single letters are taken for Windows drive letters rather than schemes.
+fn url_scheme(connection_string: &str) -> Option<&str> {
+    let (scheme, _) = connection_string.split_once(':')?;
+    let is_scheme = scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    is_scheme.then_some(scheme)
+}
+
+/// The backends that connection strings are matched against.
+pub struct Registry {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl Default for Registry {
+    /// The built-in backends, with SQLite taking any string the others don't recognize.
+    fn default() -> Self {
+        Self {
+            backends: vec![
+                Box::new(PostgresBackend),
+                Box::new(MysqlBackend),
+                Box::new(DuckdbBackend),
+                Box::new(SqliteBackend),
+            ],
+        }
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.backends.iter().map(|backend| backend.schemes()))
+            .finish()
+    }
+}
+
+impl Registry {
+    /// A registry without any backends.
+    pub fn empty() -> Self {
+        Self { backends: vec![] }
+    }
+
+    /// Adds a backend, taking precedence over those registered before it.
+    pub fn register(&mut self, backend: impl Backend + 'static) {
+        self.backends.insert(0, Box::new(backend));
+    }
+
+    /// Connects with the backend claiming the connection string's URL scheme, or else
+    /// the first that accepts it.
+    pub fn open(&self, connection_string: &str, read_only: bool) -> Result<Database> {
+        let by_scheme = url_scheme(connection_string).and_then(|scheme| {
+            self.backends.iter().find(|backend| {
+                backend
+                    .schemes()
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(scheme))
+            })
+        });
+        let backend = by_scheme
+            .or_else(|| {
+                self.backends
+                    .iter()
+                    .find(|backend| backend.accepts(connection_string))
+            })
+            .with_context(|| {
+                format!(
+                    "No database backend recognizes {}",
+                    redact_password(connection_string)
+                )
+            })?;
+
+        let mut database = Database::new(backend.connect(connection_string, read_only)?);
+        database.opened_from = Some(connection_string.to_string());
+        Ok(database)
+    }
+}
+
+/// An open connection, whichever backend made it.
+pub struct Database {
+    connection: Box<dyn DatabaseConnection>,
+    opened_from: Option<String>,
+}
+
+impl Database {
+    /// Connects with one of the built-in backends, see [`Registry::open`].
+    pub fn open(connection_string: &str, read_only: bool) -> Result<Self> {
+        Registry::default().open(connection_string, read_only)
+    }
+
+    pub fn new(connection: Box<dyn DatabaseConnection>) -> Self {
+        Self {
+            connection,
+            opened_from: None,
+        }
+    }
+
+    pub fn load_schema(&self) -> Result<Schema> {
+        self.connection.load_schema()
+    }
+
+    pub fn definition(&self, kind: ObjectKind, schema: Option<&str>, name: &str) -> Result<String> {
+        self.connection.definition(kind, schema, name)
+    }
+
+    pub fn stream_query(&self, sql: &str, first_batch: usize, sink: &mut RowSink) -> QueryResult {
+        self.connection.stream_query(sql, first_batch, sink)
+    }
+
+    /// Runs a statement, reading up to `max_rows` of its rows into the result.
+    pub fn execute_query(&self, sql: &str, max_rows: usize) -> QueryResult {
+        let mut rows = Vec::new();
+        let mut result = self.stream_query(sql, max_rows, &mut |_, batch| {
+            rows.extend(batch);
+            max_rows.saturating_sub(rows.len())
+        });
+        result.row_count = rows.len();
+        result.rows = rows;
+        result
+    }
+
+    pub fn transaction_state(&self) -> TransactionState {
+        self.connection.transaction_state()
+    }
+
+    pub fn begin_transaction(&self) -> Result<()> {
+        self.connection.begin_transaction()
+    }
+
+    pub fn end_transaction(&self, commit: bool) -> Result<()> {
+        self.connection.end_transaction(commit)
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.connection.cancel_handle()
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.connection.dialect()
+    }
+
+    pub fn get_display_name(&self) -> String {
+        self.connection.get_display_name()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.connection.is_read_only()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.connection.is_closed()
+    }
+
+    pub fn path(&self) -> String {
+        self.get_display_name()
+    }
+
+    /// The connection string the registry opened this from, to open it again when
+    /// reconnecting, or the display name for connections made some other way.
+    pub fn reopen_target(&self) -> String {
+        self.opened_from.clone().unwrap_or_else(|| self.path())
+    }
+
+    /// Runs a `SELECT COUNT(*)` query and returns the count. Inside a transaction it runs
+    /// in a savepoint, so a failing count doesn't spoil the transaction, or not at all
+    /// where the database has no savepoints.
+    pub fn count_rows(&self, sql: &str) -> Option<u64> {
+        let in_transaction = match self.transaction_state() {
+            TransactionState::Idle => false,
+            TransactionState::Open if self.dialect().savepoints => true,
+            TransactionState::Open | TransactionState::Failed => return None,
+        };
+        let execute = |sql: &str| self.stream_query(sql, 0, &mut |_, _| 0).error.is_none();
+        if in_transaction && !execute("SAVEPOINT sqlclix_count") {
+            return None;
+        }
+        let mut count = None;
+        self.stream_query(sql, 1, &mut |_, rows| {
+            if let Some(CellValue::Int(n)) = rows.first().and_then(|row| row.first()) {
+                count = u64::try_from(*n).ok();
+            }
+            0
+        });
+        if in_transaction {
+            execute("ROLLBACK TO SAVEPOINT sqlclix_count");
+            execute("RELEASE SAVEPOINT sqlclix_count");
+        }
+        count
     }
 }
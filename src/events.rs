@@ -1,4 +1,4 @@
-use crate::app::{App, Panel};
+use crate::app::{App, Dialog, Panel};
 use crate::ui::results::{get_json_line_count, get_selected_json_path};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use std::time::Duration;
@@ -12,6 +12,13 @@ pub fn poll_event(timeout: Duration) -> std::io::Result<Option<Event>> {
 }
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) {
+    app.status_message = None;
+
+    if let Some(dialog) = app.dialog {
+        handle_dialog_key(app, dialog, key);
+        return;
+    }
+
     // Handle cell detail / JSON viewer
     if app.show_cell_detail || app.show_row_detail {
         handle_cell_detail_key(app, key);
@@ -20,11 +27,11 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
 
     // Global shortcuts (work in any panel)
     match (key.modifiers, key.code) {
-        (KeyModifiers::NONE, KeyCode::F(1)) | (KeyModifiers::NONE, KeyCode::Char('?')) => {
-            if app.focus != Panel::Editor || key.code == KeyCode::F(1) {
-                app.show_help = !app.show_help;
-                return;
-            }
+        (KeyModifiers::NONE, KeyCode::F(1)) | (KeyModifiers::NONE, KeyCode::Char('?'))
+            if app.focus != Panel::Editor || key.code == KeyCode::F(1) =>
+        {
+            app.show_help = !app.show_help;
+            return;
         }
         (KeyModifiers::NONE, KeyCode::Esc) => {
             if app.show_help {
@@ -37,14 +44,17 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             }
         }
         (KeyModifiers::CONTROL, KeyCode::Char('c')) => {
-            app.running = false;
+            // First Ctrl+C stops a running query, like psql
+            if !app.cancel_query() {
+                app.request_quit();
+            }
             return;
         }
-        (KeyModifiers::NONE, KeyCode::Char('q')) => {
-            if app.focus != Panel::Editor && !app.show_help {
-                app.running = false;
-                return;
-            }
+        (KeyModifiers::NONE, KeyCode::Char('q'))
+            if app.focus != Panel::Editor && !app.show_help =>
+        {
+            app.request_quit();
+            return;
         }
         (KeyModifiers::NONE, KeyCode::F(5))
         | (KeyModifiers::CONTROL, KeyCode::Enter)
@@ -52,6 +62,30 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             app.execute_query();
             return;
         }
+        (KeyModifiers::NONE, KeyCode::F(6)) | (KeyModifiers::ALT, KeyCode::Enter) => {
+            app.execute_current_statement();
+            return;
+        }
+        (KeyModifiers::NONE, KeyCode::F(4)) => {
+            app.toggle_confirm_destructive();
+            return;
+        }
+        (KeyModifiers::NONE, KeyCode::F(7)) => {
+            app.toggle_transaction_mode();
+            return;
+        }
+        (KeyModifiers::NONE, KeyCode::F(8)) => {
+            app.end_transaction(true);
+            return;
+        }
+        (KeyModifiers::NONE, KeyCode::F(9)) => {
+            app.end_transaction(false);
+            return;
+        }
+        (KeyModifiers::NONE, KeyCode::F(3)) => {
+            app.reconnect();
+            return;
+        }
         (KeyModifiers::NONE, KeyCode::F(2)) => {
             app.new_tab();
             app.focus = Panel::Editor;
@@ -71,11 +105,9 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             app.prev_tab();
             return;
         }
-        (KeyModifiers::NONE, KeyCode::Tab) => {
-            if !app.show_help {
-                app.focus = app.focus.next();
-                return;
-            }
+        (KeyModifiers::NONE, KeyCode::Tab) if !app.show_help => {
+            app.focus = app.focus.next();
+            return;
         }
         (_, KeyCode::BackTab) => {
             // Shift+Tab or Ctrl+Shift+Tab
@@ -118,9 +150,14 @@ fn handle_sidebar_key(app: &mut App, key: KeyEvent) {
             app.sidebar_down();
         }
         (KeyModifiers::NONE, KeyCode::Enter) | (KeyModifiers::NONE, KeyCode::Right) => {
-            let item = app.sidebar_items.get(app.sidebar_selected);
+            let connection = app.connection();
+            let item = connection.sidebar_items.get(connection.sidebar_selected);
             if let Some(item) = item {
-                if item.is_expanded || item.children.is_empty() {
+                if let Some(group) = connection.sidebar_group.and_then(|g| item.groups.get(g)) {
+                    if !group.is_expanded {
+                        app.toggle_sidebar_expand();
+                    }
+                } else if item.is_expanded || !item.has_children() {
                     app.generate_select_query();
                 } else {
                     app.toggle_sidebar_expand();
@@ -128,10 +165,15 @@ fn handle_sidebar_key(app: &mut App, key: KeyEvent) {
             }
         }
         (KeyModifiers::NONE, KeyCode::Left) => {
-            if let Some(item) = app.sidebar_items.get(app.sidebar_selected)
-                && item.is_expanded
-            {
-                app.toggle_sidebar_expand();
+            let connection = app.connection();
+            if let Some(item) = connection.sidebar_items.get(connection.sidebar_selected) {
+                match connection.sidebar_group.and_then(|g| item.groups.get(g)) {
+                    Some(group) if group.is_expanded => app.toggle_sidebar_expand(),
+                    // Back up to the table the group belongs to
+                    Some(_) => app.connection_mut().sidebar_group = None,
+                    None if item.is_expanded => app.toggle_sidebar_expand(),
+                    None => {}
+                }
             }
         }
         (KeyModifiers::NONE, KeyCode::Char(' ')) => {
@@ -149,6 +191,9 @@ fn handle_sidebar_key(app: &mut App, key: KeyEvent) {
         (KeyModifiers::NONE, KeyCode::Char('r')) => {
             let _ = app.refresh_schema();
         }
+        (KeyModifiers::NONE, KeyCode::Char('.')) => {
+            app.toggle_system_schemas();
+        }
         _ => {}
     }
 }
@@ -206,6 +251,18 @@ fn handle_results_key(app: &mut App, key: KeyEvent) {
         (KeyModifiers::NONE, KeyCode::Char('v')) => {
             app.toggle_row_detail();
         }
+        (KeyModifiers::NONE, KeyCode::Char('f')) => {
+            app.follow_foreign_key();
+        }
+        (KeyModifiers::NONE, KeyCode::Char('r')) => {
+            app.find_referencing_rows();
+        }
+        (KeyModifiers::NONE, KeyCode::Char(']')) => {
+            app.next_statement_result();
+        }
+        (KeyModifiers::NONE, KeyCode::Char('[')) => {
+            app.prev_statement_result();
+        }
         (KeyModifiers::NONE, KeyCode::PageUp) => {
             for _ in 0..10 {
                 app.result_move_up();
@@ -227,6 +284,25 @@ fn handle_results_key(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_dialog_key(app: &mut App, dialog: Dialog, key: KeyEvent) {
+    match (dialog, key.code) {
+        (Dialog::QuitWithTransaction, KeyCode::Char('c')) => {
+            app.end_transaction_and_quit(true);
+        }
+        (Dialog::QuitWithTransaction, KeyCode::Char('r')) => {
+            app.end_transaction_and_quit(false);
+        }
+        (Dialog::ConfirmDestructive, KeyCode::Char('y') | KeyCode::Enter) => {
+            app.answer_pending_run(true);
+        }
+        (Dialog::ConfirmDestructive, KeyCode::Char('n') | KeyCode::Esc) => {
+            app.answer_pending_run(false);
+        }
+        (_, KeyCode::Esc) => app.dialog = None,
+        _ => {}
+    }
+}
+
 fn handle_cell_detail_key(app: &mut App, key: KeyEvent) {
     let line_count = get_json_line_count(app);
 
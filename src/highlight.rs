@@ -552,7 +552,7 @@ const SQL_TYPES: &[&str] = &[
 ];
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum TokenType {
+pub enum TokenType {
     Keyword,
     Function,
     Type,
@@ -565,9 +565,9 @@ enum TokenType {
 }
 
 #[derive(Debug)]
-struct Token {
-    text: String,
-    token_type: TokenType,
+pub struct Token {
+    pub text: String,
+    pub token_type: TokenType,
 }
 
 pub struct SqlHighlighter {
@@ -602,7 +602,7 @@ impl SqlHighlighter {
     }
 
     pub fn highlight_line(&self, line: &str) -> Line<'static> {
-        let tokens = self.tokenize(line);
+        let tokens = tokenize(line);
         let spans: Vec<Span> = tokens
             .into_iter()
             .map(|token| {
@@ -621,258 +621,275 @@ impl SqlHighlighter {
             .collect();
         Line::from(spans)
     }
+}
 
-    fn tokenize(&self, input: &str) -> Vec<Token> {
-        let mut tokens = Vec::new();
-        let chars: Vec<char> = input.chars().collect();
-        let mut i = 0;
+/// Splits SQL text into tokens. Comments, strings and dollar-quoted bodies stay whole,
+/// so callers can find statement boundaries as well as highlight.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    tokenize_with(input, false)
+}
 
-        while i < chars.len() {
-            let ch = chars[i];
+/// Like [`tokenize`], but `backslash_escapes` makes `\` escape the next character in
+/// every `'...'` string, as MySQL does. It always does in a Postgres `E'...'` string.
+pub fn tokenize_with(input: &str, backslash_escapes: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
 
-            // Whitespace
-            if ch.is_whitespace() {
-                let start = i;
-                while i < chars.len() && chars[i].is_whitespace() {
-                    i += 1;
-                }
-                tokens.push(Token {
-                    text: chars[start..i].iter().collect(),
-                    token_type: TokenType::Whitespace,
-                });
-                continue;
+    while i < chars.len() {
+        let ch = chars[i];
+
+        // Whitespace
+        if ch.is_whitespace() {
+            let start = i;
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
             }
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                token_type: TokenType::Whitespace,
+            });
+            continue;
+        }
 
-            // Single-line comment (--)
-            if ch == '-' && i + 1 < chars.len() && chars[i + 1] == '-' {
-                let text: String = chars[i..].iter().collect();
-                tokens.push(Token {
-                    text,
-                    token_type: TokenType::Comment,
-                });
-                break;
+        // Single-line comment (--)
+        if ch == '-' && i + 1 < chars.len() && chars[i + 1] == '-' {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                token_type: TokenType::Comment,
+            });
+            continue;
+        }
+
+        // Block comment start (/* ... */)
+        if ch == '/' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            let start = i;
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
             }
+            if i + 1 < chars.len() {
+                i += 2; // skip */
+            }
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                token_type: TokenType::Comment,
+            });
+            continue;
+        }
 
-            // Block comment start (/* ... */)
-            if ch == '/' && i + 1 < chars.len() && chars[i + 1] == '*' {
-                let start = i;
-                i += 2;
-                while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+        // PostgreSQL dollar-quoted strings ($$...$$, $tag$...$tag$)
+        if ch == '$'
+            && i + 1 < chars.len()
+            && (chars[i + 1] == '$' || chars[i + 1].is_alphabetic() || chars[i + 1] == '_')
+        {
+            // Find the tag: $$ or $tag$
+            let start = i;
+            i += 1;
+            if chars[i] != '$' {
+                while i < chars.len() && chars[i] != '$' {
+                    if !chars[i].is_alphanumeric() && chars[i] != '_' {
+                        break;
+                    }
                     i += 1;
                 }
-                if i + 1 < chars.len() {
-                    i += 2; // skip */
+            }
+            if i < chars.len() && chars[i] == '$' {
+                i += 1;
+                let tag: String = chars[start..i].iter().collect();
+                let tag_len = tag.len();
+                // Find closing tag
+                let mut found = false;
+                while i + tag_len <= chars.len() {
+                    let candidate: String = chars[i..i + tag_len].iter().collect();
+                    if candidate == tag {
+                        i += tag_len;
+                        found = true;
+                        break;
+                    }
+                    i += 1;
+                }
+                if !found {
+                    i = chars.len();
                 }
                 tokens.push(Token {
                     text: chars[start..i].iter().collect(),
-                    token_type: TokenType::Comment,
+                    token_type: TokenType::String,
                 });
                 continue;
             }
+            // Not a valid dollar-quote, treat $ as operator
+            i = start;
+            i += 1;
+            tokens.push(Token {
+                text: "$".to_string(),
+                token_type: TokenType::Operator,
+            });
+            continue;
+        }
 
-            // PostgreSQL dollar-quoted strings ($$...$$, $tag$...$tag$)
-            if ch == '$'
-                && i + 1 < chars.len()
-                && (chars[i + 1] == '$' || chars[i + 1].is_alphabetic() || chars[i + 1] == '_')
-            {
-                // Find the tag: $$ or $tag$
-                let start = i;
+        // String literals, with an E prefix for Postgres escape strings
+        let escape_string = matches!(ch, 'e' | 'E') && i + 1 < chars.len() && chars[i + 1] == '\'';
+        if ch == '\'' || ch == '"' || escape_string {
+            let start = i;
+            if escape_string {
                 i += 1;
-                if chars[i] != '$' {
-                    while i < chars.len() && chars[i] != '$' {
-                        if !chars[i].is_alphanumeric() && chars[i] != '_' {
-                            break;
-                        }
+            }
+            let quote = chars[i];
+            let escapes = escape_string || (backslash_escapes && quote == '\'');
+            i += 1;
+            while i < chars.len() {
+                if escapes && chars[i] == '\\' {
+                    i += 2;
+                } else if chars[i] == quote {
+                    if i + 1 < chars.len() && chars[i + 1] == quote {
+                        i += 2; // escaped quote
+                    } else {
                         i += 1;
+                        break;
                     }
-                }
-                if i < chars.len() && chars[i] == '$' {
+                } else {
                     i += 1;
-                    let tag: String = chars[start..i].iter().collect();
-                    let tag_len = tag.len();
-                    // Find closing tag
-                    let mut found = false;
-                    while i + tag_len <= chars.len() {
-                        let candidate: String = chars[i..i + tag_len].iter().collect();
-                        if candidate == tag {
-                            i += tag_len;
-                            found = true;
-                            break;
-                        }
-                        i += 1;
-                    }
-                    if !found {
-                        i = chars.len();
-                    }
-                    tokens.push(Token {
-                        text: chars[start..i].iter().collect(),
-                        token_type: TokenType::String,
-                    });
-                    continue;
                 }
-                // Not a valid dollar-quote, treat $ as operator
-                i = start;
-                i += 1;
-                tokens.push(Token {
-                    text: "$".to_string(),
-                    token_type: TokenType::Operator,
-                });
-                continue;
             }
+            // A backslash at the very end steps past it
+            i = i.min(chars.len());
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                token_type: TokenType::String,
+            });
+            continue;
+        }
 
-            // String literals
-            if ch == '\'' || ch == '"' {
-                let quote = ch;
-                let start = i;
+        // Numbers
+        if ch.is_ascii_digit()
+            || (ch == '.' && i + 1 < chars.len() && chars[i + 1].is_ascii_digit())
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
                 i += 1;
-                while i < chars.len() {
-                    if chars[i] == quote {
-                        if i + 1 < chars.len() && chars[i + 1] == quote {
-                            i += 2; // escaped quote
-                        } else {
-                            i += 1;
-                            break;
-                        }
-                    } else {
-                        i += 1;
-                    }
+            }
+            // Handle scientific notation
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
                 }
-                tokens.push(Token {
-                    text: chars[start..i].iter().collect(),
-                    token_type: TokenType::String,
-                });
-                continue;
             }
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                token_type: TokenType::Number,
+            });
+            continue;
+        }
 
-            // Numbers
-            if ch.is_ascii_digit()
-                || (ch == '.' && i + 1 < chars.len() && chars[i + 1].is_ascii_digit())
-            {
-                let start = i;
-                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+        // Identifiers and keywords
+        if ch.is_alphabetic() || ch == '_' || ch == '`' || ch == '[' {
+            let start = i;
+            let is_quoted = ch == '`' || ch == '[';
+            let end_quote = if ch == '[' { ']' } else { '`' };
+
+            if is_quoted {
+                i += 1;
+                while i < chars.len() && chars[i] != end_quote {
                     i += 1;
                 }
-                // Handle scientific notation
-                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                if i < chars.len() {
                     i += 1;
-                    if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
-                        i += 1;
-                    }
-                    while i < chars.len() && chars[i].is_ascii_digit() {
-                        i += 1;
-                    }
                 }
                 tokens.push(Token {
                     text: chars[start..i].iter().collect(),
-                    token_type: TokenType::Number,
+                    token_type: TokenType::Identifier,
                 });
-                continue;
-            }
-
-            // Identifiers and keywords
-            if ch.is_alphabetic() || ch == '_' || ch == '`' || ch == '[' {
-                let start = i;
-                let is_quoted = ch == '`' || ch == '[';
-                let end_quote = if ch == '[' { ']' } else { '`' };
-
-                if is_quoted {
+            } else {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
                     i += 1;
-                    while i < chars.len() && chars[i] != end_quote {
-                        i += 1;
-                    }
-                    if i < chars.len() {
-                        i += 1;
-                    }
-                    tokens.push(Token {
-                        text: chars[start..i].iter().collect(),
-                        token_type: TokenType::Identifier,
-                    });
-                } else {
-                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
-                        i += 1;
-                    }
-                    let text: String = chars[start..i].iter().collect();
-                    let upper = text.to_uppercase();
-                    let token_type = if SQL_KEYWORDS.contains(&upper.as_str()) {
-                        TokenType::Keyword
-                    } else if SQL_FUNCTIONS.contains(&upper.as_str()) {
-                        TokenType::Function
-                    } else if SQL_TYPES.contains(&upper.as_str()) {
-                        TokenType::Type
-                    } else {
-                        TokenType::Identifier
-                    };
-                    tokens.push(Token { text, token_type });
                 }
-                continue;
+                let text: String = chars[start..i].iter().collect();
+                let upper = text.to_uppercase();
+                let token_type = if SQL_KEYWORDS.contains(&upper.as_str()) {
+                    TokenType::Keyword
+                } else if SQL_FUNCTIONS.contains(&upper.as_str()) {
+                    TokenType::Function
+                } else if SQL_TYPES.contains(&upper.as_str()) {
+                    TokenType::Type
+                } else {
+                    TokenType::Identifier
+                };
+                tokens.push(Token { text, token_type });
             }
+            continue;
+        }
 
-            // Operators and punctuation
-            let op_text = match ch {
-                '(' | ')' | ',' | ';' | '.' | '*' | '+' | '-' | '/' | '%' | '=' | '<' | '>'
-                | '!' | '&' | '|' | '^' | '~' | ':' | '@' | '?' | '#' => {
-                    // Check for multi-char operators
-                    if i + 1 < chars.len() {
-                        let next = chars[i + 1];
-                        match (ch, next) {
-                            ('<', '=')
-                            | ('>', '=')
-                            | ('!', '=')
-                            | ('<', '>')
-                            | ('|', '|')
-                            | ('<', '<')
-                            | ('>', '>')
-                            | (':', ':')  // PostgreSQL cast
-                            | ('@', '>')  // PostgreSQL contains
-                            | ('<', '@')  // PostgreSQL contained by
-                            | ('?', '|')  // PostgreSQL jsonb any key
-                            | ('?', '&')  // PostgreSQL jsonb all keys
-                            => {
-                                i += 2;
-                                format!("{}{}", ch, next)
-                            }
-                            ('#', '>') => {
-                                // Could be #> or #>>
-                                if i + 2 < chars.len() && chars[i + 2] == '>' {
-                                    i += 3;
-                                    "#>>".to_string()
-                                } else {
-                                    i += 2;
-                                    "#>".to_string()
-                                }
-                            }
-                            ('-', '>') => {
-                                // Could be -> or ->>
-                                if i + 2 < chars.len() && chars[i + 2] == '>' {
-                                    i += 3;
-                                    "->>".to_string()
-                                } else {
-                                    i += 2;
-                                    "->".to_string()
-                                }
-                            }
-                            _ => {
-                                i += 1;
-                                ch.to_string()
-                            }
+        // Operators and punctuation
+        let op_text = match ch {
+            '(' | ')' | ',' | ';' | '.' | '*' | '+' | '-' | '/' | '%' | '=' | '<' | '>' | '!'
+            | '&' | '|' | '^' | '~' | ':' | '@' | '?' | '#'
+                if i + 1 < chars.len() =>
+            {
+                // Check for multi-char operators
+                let next = chars[i + 1];
+                match (ch, next) {
+                    ('<', '=')
+                    | ('>', '=')
+                    | ('!', '=')
+                    | ('<', '>')
+                    | ('|', '|')
+                    | ('<', '<')
+                    | ('>', '>')
+                    | (':', ':')  // PostgreSQL cast
+                    | ('@', '>')  // PostgreSQL contains
+                    | ('<', '@')  // PostgreSQL contained by
+                    | ('?', '|')  // PostgreSQL jsonb any key
+                    | ('?', '&')  // PostgreSQL jsonb all keys
+                    => {
+                        i += 2;
+                        format!("{}{}", ch, next)
+                    }
+                    ('#', '>') => {
+                        // Could be #> or #>>
+                        if i + 2 < chars.len() && chars[i + 2] == '>' {
+                            i += 3;
+                            "#>>".to_string()
+                        } else {
+                            i += 2;
+                            "#>".to_string()
                         }
-                    } else {
+                    }
+                    ('-', '>') => {
+                        // Could be -> or ->>
+                        if i + 2 < chars.len() && chars[i + 2] == '>' {
+                            i += 3;
+                            "->>".to_string()
+                        } else {
+                            i += 2;
+                            "->".to_string()
+                        }
+                    }
+                    _ => {
                         i += 1;
                         ch.to_string()
                     }
                 }
-                _ => {
-                    i += 1;
-                    ch.to_string()
-                }
-            };
-
-            tokens.push(Token {
-                text: op_text,
-                token_type: TokenType::Operator,
-            });
-        }
+            }
+            _ => {
+                i += 1;
+                ch.to_string()
+            }
+        };
 
-        tokens
+        tokens.push(Token {
+            text: op_text,
+            token_type: TokenType::Operator,
+        });
     }
+
+    tokens
 }
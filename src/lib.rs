@@ -0,0 +1,365 @@
+//! A SQL database browser with a TUI.
+//!
+//! To add a database, implement [`db::Backend`] and [`db::DatabaseConnection`], register
+//! the backend with a [`db::Registry`], and pass the registry to [`open`] and [`run`] in
+//! [`Options`].
+
+mod app;
+pub mod config;
+pub mod db;
+mod events;
+mod highlight;
+mod libpq;
+mod output;
+mod picker;
+mod sql;
+mod state;
+mod ui;
+pub mod value;
+mod worker;
+
+pub use app::DEFAULT_MAX_ROWS;
+pub use output::Format;
+
+use anyhow::{Context, Result};
+use app::App;
+use config::Config;
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use db::{Database, Registry};
+use libpq::redact_password;
+use picker::{Picker, PickerAction};
+use ratatui::prelude::*;
+use state::StateStore;
+use std::io::{ErrorKind, Stdout, Write, stdout};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How [`run`] presents a connection.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Most rows read for any one result
+    pub max_rows: usize,
+    /// Shown in the title bar in place of the connection string, and the name the session
+    /// is saved under, e.g. `@staging`
+    pub name: Option<String>,
+    /// Background of the name in the title bar
+    pub color: Option<Color>,
+    /// Open connections added from the picker (Ctrl+O) read-only
+    pub read_only: bool,
+    /// Backends that open connections, also those added from the picker and reopened
+    /// with F3
+    pub registry: Arc<Registry>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_rows: DEFAULT_MAX_ROWS,
+            name: None,
+            color: None,
+            read_only: false,
+            registry: Arc::new(Registry::default()),
+        }
+    }
+}
+
+impl Options {
+    /// Options for another connection opened alongside this one: the same backends,
+    /// without this one's name and color.
+    fn another(&self) -> Self {
+        Self {
+            name: None,
+            color: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Opens `target`, a connection string or `@name` for a profile from the config file.
+/// Returns `options` with the profile's name and color filled in.
+pub fn open(target: &str, read_only: bool, options: &Options) -> Result<(Database, Options)> {
+    let mut options = options.clone();
+    let db = match target.strip_prefix('@') {
+        Some(name) => {
+            let config = Config::load()?;
+            let profile = config.profile(name)?;
+            options.name = Some(target.to_string());
+            options.color = profile.color()?;
+            options.registry.open(
+                &profile.connection_string()?,
+                read_only || profile.read_only,
+            )
+        }
+        None => options.registry.open(target, read_only),
+    };
+    let db = db.with_context(|| format!("Failed to open database: {}", redact_password(target)))?;
+    Ok((db, options))
+}
+
+/// Runs the `;`-separated statements of `sql` in order, writing each result to stdout in
+/// `format` and anything else to stderr. Returns false if a statement failed, which stops
+/// the rest.
+pub fn execute(db: &Database, sql: &str, format: Format, max_rows: usize) -> Result<bool> {
+    let statements = sql::split_statements(sql, db.dialect().backslash_escapes);
+    let mut out = stdout().lock();
+    let mut written = false;
+
+    for (i, statement) in statements.iter().enumerate() {
+        let result = db.execute_query(&statement.text, max_rows);
+        if let Some(error) = &result.error {
+            match statements.len() {
+                1 => eprintln!("Error: {}", error),
+                n => eprintln!("Error in statement {} of {}: {}", i + 1, n, error),
+            }
+            return Ok(false);
+        }
+
+        if result.columns.is_empty() {
+            if let Some(summary) = result.summary() {
+                eprintln!("{}", summary);
+            }
+            continue;
+        }
+        let output = (|| {
+            if written {
+                output::gap(&mut out, format)?;
+            }
+            output::write_result(&mut out, &result, format)?;
+            out.flush()
+        })();
+        match output {
+            // Whatever reads the output has seen enough, as with `| head`
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(true),
+            output => output?,
+        }
+        written = true;
+        if result.has_more {
+            eprintln!(
+                "Stopped after {} rows; raise --max-rows for more",
+                result.row_count
+            );
+        }
+    }
+
+    Ok(true)
+}
+
+/// Browses `db` in the terminal until the user quits. The session's tabs are restored on
+/// start and saved on exit.
+pub fn run(db: Database, options: &Options) -> Result<()> {
+    // Open state store
+    let state_store = StateStore::open().ok();
+
+    // Create app and restore state
+    let mut app = new_app(db, options, state_store.as_ref())?;
+
+    let mut terminal = enter_terminal()?;
+    let result = browse(&mut terminal, &mut app, options, state_store.as_ref());
+    leave_terminal(&mut terminal)?;
+
+    result
+}
+
+/// Lets the user pick a profile or a recently opened database, or type a connection
+/// string, and browses it as [`run`] does. Returns if the user quits the picker instead.
+pub fn run_picker(read_only: bool, options: &Options) -> Result<()> {
+    let state_store = StateStore::open().ok();
+    let mut picker = new_picker(state_store.as_ref());
+
+    let mut terminal = enter_terminal()?;
+    let result = pick(
+        &mut terminal,
+        &mut picker,
+        read_only,
+        options,
+        state_store.as_ref(),
+    )
+    .and_then(|app| match app {
+        Some(mut app) => browse(&mut terminal, &mut app, options, state_store.as_ref()),
+        None => Ok(()),
+    });
+    leave_terminal(&mut terminal)?;
+
+    result
+}
+
+/// Number of recently opened databases the picker lists.
+const RECENT_CONNECTIONS: usize = 20;
+
+fn new_app(db: Database, options: &Options, state_store: Option<&StateStore>) -> Result<App> {
+    let mut app = App::new(db, options.name.clone(), options.color, state_store)?;
+    app.max_rows = options.max_rows;
+    app.registry = Arc::clone(&options.registry);
+    if let Some(store) = state_store {
+        let _ = store.record_recent(&app.connection().db_path);
+    }
+    Ok(app)
+}
+
+/// A picker listing the profiles and recently opened databases. A broken config file
+/// leaves only the recent ones, with its error shown.
+fn new_picker(state_store: Option<&StateStore>) -> Picker {
+    let (config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(format!("{:#}", e))),
+    };
+    let recent = state_store
+        .and_then(|store| store.recent_connections(RECENT_CONNECTIONS).ok())
+        .unwrap_or_default();
+    let mut picker = Picker::new(&config, recent);
+    picker.error = config_error;
+    picker
+}
+
+fn enter_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+    Ok(terminal)
+}
+
+fn leave_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Runs the picker until a connection opens, or the user quits.
+fn pick<B: Backend>(
+    terminal: &mut Terminal<B>,
+    picker: &mut Picker,
+    read_only: bool,
+    options: &Options,
+    state_store: Option<&StateStore>,
+) -> Result<Option<App>> {
+    loop {
+        terminal.draw(|frame| ui::render_picker(frame, picker))?;
+
+        let Some(Event::Key(key)) = events::poll_event(Duration::from_millis(100))? else {
+            continue;
+        };
+        match picker.handle_key(key) {
+            PickerAction::None => {}
+            PickerAction::Quit => return Ok(None),
+            PickerAction::Open(target) => {
+                // Connecting blocks, so say what's happening first
+                picker.connecting = Some(redact_password(&target));
+                terminal.draw(|frame| ui::render_picker(frame, picker))?;
+                let app = open(&target, read_only, options)
+                    .and_then(|(db, options)| new_app(db, &options, state_store));
+                picker.connecting = None;
+                match app {
+                    Ok(app) => return Ok(Some(app)),
+                    Err(e) => picker.error = Some(format!("{:#}", e)),
+                }
+            }
+        }
+    }
+}
+
+/// Runs the app until the user quits, then saves the session.
+fn browse<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    options: &Options,
+    state_store: Option<&StateStore>,
+) -> Result<()> {
+    let result = run_app(terminal, app, options, state_store);
+
+    // Save state before exit
+    if let Some(store) = state_store {
+        let _ = app.save_state(store);
+    }
+
+    result
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    options: &Options,
+    state_store: Option<&StateStore>,
+) -> Result<()> {
+    while app.running {
+        // Pick up results from background queries
+        app.poll_queries();
+
+        // Draw
+        terminal.draw(|frame| match &app.picker {
+            Some(picker) => ui::render_picker(frame, picker),
+            None => ui::render(frame, app),
+        })?;
+
+        // Handle events
+        if let Some(event) = events::poll_event(Duration::from_millis(100))? {
+            match event {
+                Event::Key(key) if app.picker.is_some() => {
+                    handle_picker_key(terminal, app, key, options, state_store)?;
+                }
+                Event::Key(key)
+                    if key.modifiers == KeyModifiers::CONTROL
+                        && key.code == KeyCode::Char('o')
+                        && app.dialog.is_none() =>
+                {
+                    app.picker = Some(new_picker(state_store));
+                }
+                Event::Key(key) => events::handle_key_event(app, key),
+                Event::Resize(_, _) => {}
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles a key for the picker opened over the app, adding the connection picked, or
+/// switching to it if it is open already.
+fn handle_picker_key<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    key: KeyEvent,
+    options: &Options,
+    state_store: Option<&StateStore>,
+) -> Result<()> {
+    let Some(mut picker) = app.picker.take() else {
+        return Ok(());
+    };
+    match picker.handle_key(key) {
+        PickerAction::None => app.picker = Some(picker),
+        PickerAction::Quit => {}
+        PickerAction::Open(target) => {
+            if app.switch_to_connection(&target) {
+                return Ok(());
+            }
+            picker.connecting = Some(redact_password(&target));
+            terminal.draw(|frame| ui::render_picker(frame, &picker))?;
+            let added =
+                open(&target, options.read_only, &options.another()).and_then(|(db, opened)| {
+                    app.add_connection(db, opened.name, opened.color, state_store)
+                });
+            match added {
+                Ok(()) => {
+                    if let Some(store) = state_store {
+                        let _ = store.record_recent(&app.connection().db_path);
+                    }
+                    app.focus = app::Panel::Editor;
+                }
+                Err(e) => {
+                    picker.connecting = None;
+                    picker.error = Some(format!("{:#}", e));
+                    app.picker = Some(picker);
+                }
+            }
+        }
+    }
+    Ok(())
+}
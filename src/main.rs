@@ -1,88 +1,95 @@
-mod app;
-mod db;
-mod events;
-mod highlight;
-mod state;
-mod ui;
-
 use anyhow::{Context, Result};
-use app::App;
 use clap::Parser;
-use crossterm::{
-    event::Event,
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
-use db::Database;
-use ratatui::prelude::*;
-use state::StateStore;
-use std::io::stdout;
-use std::time::Duration;
+use sqlclix::{Format, Options};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "sqlclix")]
 #[command(
     author,
     version,
-    about = "A SQL database browser with TUI interface (SQLite & PostgreSQL)"
+    about = "A SQL database browser with TUI interface (SQLite, PostgreSQL, MySQL & DuckDB)"
 )]
 struct Cli {
-    /// SQLite database file path or PostgreSQL connection string
+    /// SQLite or DuckDB database file path, `duckdb:` for in-memory DuckDB, a PostgreSQL
+    /// or MySQL connection string, or `@name` for a connection from the config file.
+    /// Without one, a picker lists configured and recently opened connections
     #[arg(value_name = "DATABASE")]
-    database: String,
-}
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-
-    // Open state store
-    let state_store = StateStore::open().ok();
-
-    // Open database
-    let db = Database::open(&cli.database)
-        .with_context(|| format!("Failed to open database: {}", cli.database))?;
-
-    // Create app and restore state
-    let mut app = App::new(db, state_store.as_ref())?;
+    database: Option<String>,
 
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
+    /// Maximum number of rows fetched for a single result
+    #[arg(long, value_name = "ROWS", default_value_t = sqlclix::DEFAULT_MAX_ROWS)]
+    max_rows: usize,
 
-    // Run app
-    let result = run_app(&mut terminal, &mut app);
+    /// Open the database read-only; a missing SQLite file is an error rather than created
+    #[arg(long)]
+    read_only: bool,
 
-    // Save state before exit
-    if let Some(store) = &state_store {
-        let _ = app.save_state(store);
-    }
+    /// Run SQL and print its results instead of opening the browser
+    #[arg(
+        short,
+        long,
+        value_name = "SQL",
+        requires = "database",
+        conflicts_with = "file"
+    )]
+    execute: Option<String>,
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    /// Run the SQL in a file, or `-` for stdin, and print its results
+    #[arg(short, long, value_name = "PATH", requires = "database")]
+    file: Option<PathBuf>,
 
-    result
+    /// How -e and -f print results
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
-    while app.running {
-        // Draw
-        terminal.draw(|frame| ui::render(frame, app))?;
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let options = Options {
+        max_rows: cli.max_rows,
+        read_only: cli.read_only,
+        ..Default::default()
+    };
 
-        // Handle events
-        if let Some(event) = events::poll_event(Duration::from_millis(100))? {
-            match event {
-                Event::Key(key) => events::handle_key_event(app, key),
-                Event::Resize(_, _) => {}
-                _ => {}
-            }
+    let script = match (&cli.execute, &cli.file) {
+        (Some(sql), _) => Some(sql.clone()),
+        (None, Some(path)) if path.as_os_str() == "-" => {
+            let mut sql = String::new();
+            std::io::stdin()
+                .read_to_string(&mut sql)
+                .context("Failed to read SQL from stdin")?;
+            Some(sql)
         }
-    }
+        (None, Some(path)) => Some(
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        ),
+        (None, None) => None,
+    };
 
-    Ok(())
+    match (&cli.database, script) {
+        // Print results and exit, without taking over the terminal
+        (Some(database), Some(sql)) => {
+            let (db, options) = sqlclix::open(database, cli.read_only, &options)?;
+            Ok(
+                if sqlclix::execute(&db, &sql, cli.format, options.max_rows)? {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                },
+            )
+        }
+        (Some(database), None) => {
+            let (db, options) = sqlclix::open(database, cli.read_only, &options)?;
+            sqlclix::run(db, &options)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        (None, _) => {
+            sqlclix::run_picker(cli.read_only, &options)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
 }
@@ -0,0 +1,334 @@
+//! Results written out as text, for `sqlclix DATABASE -e SQL`.
+
+use crate::db::QueryResult;
+use crate::value::CellValue;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+/// How results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Csv,
+    /// Tab-separated, with tabs, newlines and backslashes escaped
+    Tsv,
+    /// An array of objects per result
+    Json,
+    /// An object per row, one per line
+    Jsonl,
+    Markdown,
+    /// Aligned columns, as psql prints them
+    Table,
+}
+
+/// Writes a result that has columns. Results after the first are set apart with `gap`.
+pub fn write_result(out: &mut dyn Write, result: &QueryResult, format: Format) -> io::Result<()> {
+    match format {
+        Format::Csv => write_delimited(out, result, ',', csv_field),
+        Format::Tsv => write_delimited(out, result, '\t', tsv_field),
+        Format::Json => {
+            if result.rows.is_empty() {
+                return writeln!(out, "[]");
+            }
+            writeln!(out, "[")?;
+            for (i, row) in result.rows.iter().enumerate() {
+                let comma = if i + 1 < result.rows.len() { "," } else { "" };
+                writeln!(out, "  {}{}", json_object(result, row), comma)?;
+            }
+            writeln!(out, "]")
+        }
+        Format::Jsonl => {
+            for row in &result.rows {
+                writeln!(out, "{}", json_object(result, row))?;
+            }
+            Ok(())
+        }
+        Format::Markdown => write_markdown(out, result),
+        Format::Table => write_table(out, result),
+    }
+}
+
+/// Separates one result from the next.
+pub fn gap(out: &mut dyn Write, format: Format) -> io::Result<()> {
+    match format {
+        // Each line stands on its own already
+        Format::Jsonl => Ok(()),
+        _ => writeln!(out),
+    }
+}
+
+/// The value as data: NULL is empty, and bytes are hex as Postgres writes them.
+fn plain(value: &CellValue) -> String {
+    match value {
+        CellValue::Null => String::new(),
+        CellValue::Bytes(bytes) => {
+            let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            format!("\\x{}", hex)
+        }
+        value => value.to_string(),
+    }
+}
+
+fn write_delimited(
+    out: &mut dyn Write,
+    result: &QueryResult,
+    separator: char,
+    field: fn(&str) -> String,
+) -> io::Result<()> {
+    let line = |fields: Vec<String>| fields.join(&separator.to_string());
+    writeln!(
+        out,
+        "{}",
+        line(result.columns.iter().map(|c| field(&c.name)).collect())
+    )?;
+    for row in &result.rows {
+        writeln!(
+            out,
+            "{}",
+            line(row.iter().map(|v| field(&plain(v))).collect())
+        )?;
+    }
+    Ok(())
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// A row as a JSON object with its columns in order. Text stays a string even when it
+/// holds JSON; JSON columns are written as they are.
+fn json_object(result: &QueryResult, row: &[CellValue]) -> String {
+    let fields: Vec<String> = result
+        .columns
+        .iter()
+        .zip(row)
+        .map(|(column, value)| {
+            let value = match value {
+                CellValue::Text(_) | CellValue::Bytes(_) => serde_json::Value::from(plain(value)),
+                value => value.to_json(),
+            };
+            format!(
+                "{}:{}",
+                serde_json::Value::from(column.name.as_str()),
+                value
+            )
+        })
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Columns holding only numbers (or NULL) are aligned right.
+fn numeric_columns(result: &QueryResult) -> Vec<bool> {
+    (0..result.columns.len())
+        .map(|i| {
+            result.rows.iter().any(|row| row[i].is_numeric())
+                && result
+                    .rows
+                    .iter()
+                    .all(|row| row[i].is_numeric() || row[i].is_null())
+        })
+        .collect()
+}
+
+fn write_markdown(out: &mut dyn Write, result: &QueryResult) -> io::Result<()> {
+    let cell = |text: &str| {
+        text.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+    let header: Vec<String> = result.columns.iter().map(|c| cell(&c.name)).collect();
+    writeln!(out, "| {} |", header.join(" | "))?;
+    let rules: Vec<&str> = numeric_columns(result)
+        .into_iter()
+        .map(|numeric| if numeric { "---:" } else { "---" })
+        .collect();
+    writeln!(out, "| {} |", rules.join(" | "))?;
+    for row in &result.rows {
+        let cells: Vec<String> = row.iter().map(|v| cell(&v.to_string())).collect();
+        writeln!(out, "| {} |", cells.join(" | "))?;
+    }
+    Ok(())
+}
+
+fn write_table(out: &mut dyn Write, result: &QueryResult) -> io::Result<()> {
+    let cell = |value: &CellValue| value.to_string().replace('\n', "\\n");
+    let rows: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| row.iter().map(cell).collect())
+        .collect();
+    let widths: Vec<usize> = result
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].width())
+                .chain([column.name.width()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let numeric = numeric_columns(result);
+
+    let pad = |text: &str, i: usize, right: bool| {
+        let fill = " ".repeat(widths[i] - text.width());
+        if right {
+            format!("{}{}", fill, text)
+        } else {
+            format!("{}{}", text, fill)
+        }
+    };
+    let header: Vec<String> = result
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| pad(&column.name, i, false))
+        .collect();
+    writeln!(out, " {}", header.join(" | ").trim_end())?;
+    let rules: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+    writeln!(out, "{}", rules.join("+"))?;
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, text)| pad(text, i, numeric[i]))
+            .collect();
+        writeln!(out, " {}", cells.join(" | ").trim_end())?;
+    }
+    match rows.len() {
+        1 => writeln!(out, "(1 row)"),
+        n => writeln!(out, "({} rows)", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ResultColumn;
+
+    fn result(columns: &[&str], rows: Vec<Vec<CellValue>>) -> QueryResult {
+        let columns = columns
+            .iter()
+            .map(|name| ResultColumn {
+                name: name.to_string(),
+                data_type: String::new(),
+            })
+            .collect();
+        let mut result = QueryResult::empty(columns);
+        result.row_count = rows.len();
+        result.rows = rows;
+        result
+    }
+
+    fn written(result: &QueryResult, format: Format) -> String {
+        let mut out = Vec::new();
+        write_result(&mut out, result, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn text(s: &str) -> CellValue {
+        CellValue::Text(s.to_string())
+    }
+
+    #[test]
+    fn quotes_csv_fields_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn escapes_tsv_fields() {
+        assert_eq!(tsv_field("plain"), "plain");
+        assert_eq!(tsv_field("a\tb\\c\nd\re"), "a\\tb\\\\c\\nd\\re");
+    }
+
+    #[test]
+    fn writes_null_and_bytes_as_data() {
+        let result = result(
+            &["id", "note", "blob"],
+            vec![vec![
+                CellValue::Int(1),
+                CellValue::Null,
+                CellValue::Bytes(vec![0xde, 0xad]),
+            ]],
+        );
+        assert_eq!(written(&result, Format::Csv), "id,note,blob\n1,,\\xdead\n");
+        assert_eq!(
+            written(&result, Format::Tsv),
+            "id\tnote\tblob\n1\t\t\\\\xdead\n"
+        );
+    }
+
+    #[test]
+    fn writes_json_with_typed_values() {
+        let result = result(
+            &["n", "price", "doc", "raw"],
+            vec![vec![
+                CellValue::Int(7),
+                CellValue::Decimal("12.50".to_string()),
+                CellValue::Json(serde_json::json!({"a": 1})),
+                text("{\"a\": 1}"),
+            ]],
+        );
+        let object = r#"{"n":7,"price":12.5,"doc":{"a":1},"raw":"{\"a\": 1}"}"#;
+        assert_eq!(
+            written(&result, Format::Json),
+            format!("[\n  {}\n]\n", object)
+        );
+        assert_eq!(written(&result, Format::Jsonl), format!("{}\n", object));
+        assert_eq!(written(&self::result(&["n"], vec![]), Format::Json), "[]\n");
+    }
+
+    #[test]
+    fn writes_markdown_tables() {
+        let result = result(
+            &["id", "name"],
+            vec![vec![CellValue::Int(1), text("a|b\nc")]],
+        );
+        assert_eq!(
+            written(&result, Format::Markdown),
+            "| id | name |\n| ---: | --- |\n| 1 | a\\|b<br>c |\n"
+        );
+    }
+
+    #[test]
+    fn aligns_tables_by_display_width() {
+        let result = result(
+            &["id", "name"],
+            vec![
+                vec![CellValue::Int(1), text("日本")],
+                vec![CellValue::Int(22), CellValue::Null],
+                vec![CellValue::Null, text("x\ny")],
+            ],
+        );
+        assert_eq!(
+            written(&result, Format::Table),
+            [
+                " id   | name",
+                "------+------",
+                "    1 | 日本",
+                "   22 | NULL",
+                " NULL | x\\ny",
+                "(3 rows)\n",
+            ]
+            .join("\n")
+        );
+
+        let one = self::result(&["a"], vec![vec![text("b")]]);
+        assert_eq!(written(&one, Format::Table), " a\n---\n b\n(1 row)\n");
+    }
+}
@@ -0,0 +1,244 @@
+//! The connection picker shown when sqlclix starts without a database.
+
+use crate::config::{Config, Profile};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Recent,
+    Profile,
+    /// Opens the typed text as a connection string
+    New,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: EntryKind,
+    /// What to open: a connection string, or `@name` for a profile
+    pub target: String,
+    /// Where a profile connects to
+    pub detail: String,
+    pub color: Option<Color>,
+}
+
+/// What the picker wants done after a key press.
+pub enum PickerAction {
+    None,
+    Open(String),
+    Quit,
+}
+
+pub struct Picker {
+    entries: Vec<Entry>,
+    /// Filter text, which can also be opened as a connection string
+    pub input: String,
+    /// Entries matching the input, best first within each kind
+    pub visible: Vec<Entry>,
+    pub selected: usize,
+    pub error: Option<String>,
+    /// The target being opened, while the connection is made
+    pub connecting: Option<String>,
+}
+
+impl Picker {
+    pub fn new(config: &Config, recent: Vec<String>) -> Self {
+        let color = |target: &str| {
+            target
+                .strip_prefix('@')
+                .and_then(|name| config.connections.get(name))
+                .and_then(|profile| profile.color().ok().flatten())
+        };
+        let recent = recent.into_iter().map(|target| Entry {
+            kind: EntryKind::Recent,
+            color: color(&target),
+            detail: String::new(),
+            target,
+        });
+        let profiles = config.connections.iter().map(|(name, profile)| Entry {
+            kind: EntryKind::Profile,
+            target: format!("@{}", name),
+            detail: describe(profile),
+            color: profile.color().ok().flatten(),
+        });
+
+        let mut picker = Self {
+            entries: recent.chain(profiles).collect(),
+            input: String::new(),
+            visible: vec![],
+            selected: 0,
+            error: None,
+            connecting: None,
+        };
+        picker.filter();
+        picker
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PickerAction {
+        self.error = None;
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc) | (KeyModifiers::CONTROL, KeyCode::Char('c' | 'q')) => {
+                return PickerAction::Quit;
+            }
+            (_, KeyCode::Enter) => {
+                if let Some(entry) = self.visible.get(self.selected) {
+                    return PickerAction::Open(entry.target.clone());
+                }
+            }
+            (_, KeyCode::Up) | (KeyModifiers::CONTROL, KeyCode::Char('p')) => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            (_, KeyCode::Down) | (KeyModifiers::CONTROL, KeyCode::Char('n')) => {
+                self.selected = (self.selected + 1).min(self.visible.len().saturating_sub(1));
+            }
+            (_, KeyCode::Backspace) => {
+                self.input.pop();
+                self.filter();
+            }
+            (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
+                self.input.clear();
+                self.filter();
+            }
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+                self.input.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        PickerAction::None
+    }
+
+    /// Narrows the entries down to those fuzzily matching the input, offering to open
+    /// the input itself last.
+    fn filter(&mut self) {
+        let pattern = self.input.trim();
+        let mut scored: Vec<(i32, &Entry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let text = format!("{} {}", entry.target, entry.detail);
+                fuzzy_score(pattern, &text).map(|score| (score, entry))
+            })
+            .collect();
+        // Stable, so equally good matches keep their order
+        scored.sort_by_key(|(score, entry)| (entry.kind == EntryKind::Profile, -score));
+
+        self.visible = scored.into_iter().map(|(_, entry)| entry.clone()).collect();
+        self.selected = 0;
+        if pattern.is_empty() {
+            return;
+        }
+        let typed = Entry {
+            kind: EntryKind::New,
+            target: pattern.to_string(),
+            detail: String::new(),
+            color: None,
+        };
+        if let Some(exact) = self
+            .visible
+            .iter()
+            .position(|entry| entry.target == pattern)
+        {
+            self.selected = exact;
+            self.visible.push(typed);
+        } else if pattern.contains(['/', '\\', '.', ':', '=']) {
+            // A path or connection string opens as typed, not as the closest match
+            self.visible.insert(0, typed);
+        } else {
+            self.visible.push(typed);
+        }
+    }
+}
+
+/// Where a profile connects to, e.g. `app@db.example.com:5432/app`.
+fn describe(profile: &Profile) -> String {
+    if let Some(url) = &profile.url {
+        return crate::libpq::strip_password(url);
+    }
+    let mut detail = String::new();
+    if let Some(user) = &profile.user {
+        detail.push_str(&format!("{}@", user));
+    }
+    detail.push_str(profile.host.as_deref().unwrap_or("localhost"));
+    if let Some(port) = profile.port {
+        detail.push_str(&format!(":{}", port));
+    }
+    if let Some(db) = &profile.db {
+        detail.push_str(&format!("/{}", db));
+    }
+    if profile.read_only {
+        detail.push_str(" (read-only)");
+    }
+    detail
+}
+
+/// Scores `text` for containing the characters of `pattern` in order, ignoring case.
+/// Runs of consecutive characters and matches at the start of a word score higher.
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let Some(&wanted) = pattern.peek() else {
+            break;
+        };
+        let matched = c == wanted;
+        if matched {
+            pattern.next();
+            score += 1;
+            if previous_matched {
+                score += 5;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+
+    pattern.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picker(recent: &[&str], input: &str) -> Picker {
+        let recent = recent.iter().map(|target| target.to_string()).collect();
+        let mut picker = Picker::new(&Config::default(), recent);
+        picker.input = input.to_string();
+        picker.filter();
+        picker
+    }
+
+    fn selected(picker: &Picker) -> &str {
+        &picker.visible[picker.selected].target
+    }
+
+    #[test]
+    fn opens_a_typed_connection_string_over_a_close_match() {
+        let recent = ["postgres://localhost/app_staging"];
+        let typed = picker(&recent, "postgres://localhost/app");
+        assert_eq!(selected(&typed), "postgres://localhost/app");
+        assert_eq!(typed.visible[0].kind, EntryKind::New);
+    }
+
+    #[test]
+    fn selects_an_exact_match_or_else_the_best_fuzzy_one() {
+        let recent = [
+            "postgres://localhost/app_staging",
+            "postgres://localhost/app",
+        ];
+        assert_eq!(
+            selected(&picker(&recent, "postgres://localhost/app")),
+            "postgres://localhost/app"
+        );
+        assert_eq!(
+            selected(&picker(&recent, "staging")),
+            "postgres://localhost/app_staging"
+        );
+    }
+}
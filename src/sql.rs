@@ -0,0 +1,782 @@
+use crate::highlight::{TokenType, tokenize, tokenize_with};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// One statement of a script, with its byte range in the script text.
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits a script into statements at top-level semicolons.
+///
+/// Semicolons inside strings, quoted identifiers, comments and `$tag$` bodies don't
+/// count, nor do those inside the `BEGIN ... END` body of a SQLite trigger. Statements
+/// holding nothing but comments are dropped. `backslash_escapes` comes from the
+/// connection's [`Dialect`](crate::db::Dialect).
+pub fn split_statements(sql: &str, backslash_escapes: bool) -> Vec<Statement> {
+    split(sql, false, backslash_escapes)
+}
+
+/// Finds the statement around byte `offset`, where blank lines also end a statement.
+/// Between statements, the one before the offset wins.
+pub fn statement_at(sql: &str, offset: usize, backslash_escapes: bool) -> Option<Statement> {
+    let mut statements = split(sql, true, backslash_escapes);
+    let index = statements
+        .iter()
+        .rposition(|s| s.start <= offset)
+        .unwrap_or(0);
+    (index < statements.len()).then(|| statements.swap_remove(index))
+}
+
+fn split(sql: &str, blank_lines: bool, backslash_escapes: bool) -> Vec<Statement> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut words: Vec<String> = Vec::new();
+    let mut block_depth = 0usize;
+
+    for token in tokenize_with(sql, backslash_escapes) {
+        let token_start = offset;
+        offset += token.text.len();
+        match token.token_type {
+            TokenType::Whitespace
+                if blank_lines && block_depth == 0 && token.text.matches('\n').count() > 1 =>
+            {
+                push_statement(&mut statements, sql, start, token_start, &words);
+                start = offset;
+                words.clear();
+            }
+            TokenType::Whitespace | TokenType::Comment => {}
+            TokenType::Operator if token.text == ";" && block_depth == 0 => {
+                push_statement(&mut statements, sql, start, token_start, &words);
+                start = offset;
+                words.clear();
+            }
+            _ => {
+                let word = token.text.to_uppercase();
+                if is_trigger(&words) {
+                    match word.as_str() {
+                        "BEGIN" | "CASE" => block_depth += 1,
+                        "END" => block_depth = block_depth.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                words.push(word);
+            }
+        }
+    }
+    push_statement(&mut statements, sql, start, sql.len(), &words);
+    statements
+}
+
+/// The statement's first word, upper-cased and skipping comments, e.g. `SELECT`.
+pub fn first_keyword(sql: &str) -> String {
+    tokenize(sql)
+        .into_iter()
+        .find(|t| !matches!(t.token_type, TokenType::Whitespace | TokenType::Comment))
+        .map(|t| t.text.to_uppercase())
+        .unwrap_or_default()
+}
+
+/// The verb of a statement that changes rows (`INSERT`, `UPDATE`, `DELETE`, `MERGE` or
+/// `REPLACE`), looking past a leading `WITH` clause.
+pub fn dml_verb(sql: &str) -> Option<String> {
+    let mut words = tokenize(sql)
+        .into_iter()
+        .filter(|t| matches!(t.token_type, TokenType::Keyword | TokenType::Identifier))
+        .map(|t| t.text.to_uppercase());
+    let first = words.next()?;
+    let is_dml = |w: &str| matches!(w, "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "REPLACE");
+    if first == "WITH" {
+        words.find(|w| is_dml(w))
+    } else {
+        is_dml(&first).then_some(first)
+    }
+}
+
+/// The tag Postgres completes a statement with, such as `UPDATE 42` or `CREATE TABLE`,
+/// for `rows` processed. The driver only hands back the count, so the rest is named from
+/// the statement the way the server names it.
+pub fn command_tag(sql: &str, rows: u64) -> String {
+    let words: Vec<String> = significant_tokens(sql)
+        .into_iter()
+        .map(|(word, _)| word.to_uppercase())
+        .collect();
+    let word = |i: usize| words.get(i).map_or("", String::as_str);
+
+    if let Some(verb) = dml_verb(sql) {
+        // The OID field of INSERT tags is always 0 on modern servers
+        return match verb.as_str() {
+            "INSERT" => format!("INSERT 0 {}", rows),
+            verb => format!("{} {}", verb, rows),
+        };
+    }
+    match word(0) {
+        "SELECT" | "VALUES" | "TABLE" | "WITH" => format!("SELECT {}", rows),
+        "COPY" | "FETCH" | "MOVE" => format!("{} {}", word(0), rows),
+        "CREATE" => {
+            let from = words[1..]
+                .iter()
+                .position(|w| {
+                    !matches!(
+                        w.as_str(),
+                        "OR" | "REPLACE"
+                            | "TEMP"
+                            | "TEMPORARY"
+                            | "UNLOGGED"
+                            | "GLOBAL"
+                            | "LOCAL"
+                            | "UNIQUE"
+                            | "RECURSIVE"
+                            | "TRUSTED"
+                            | "PROCEDURAL"
+                            | "DEFAULT"
+                            | "CONSTRAINT"
+                    )
+                })
+                .map_or(words.len(), |i| i + 1);
+            let kind = object_type(&words[from..]);
+            // CREATE TABLE AS and materialized views report the rows they were filled with
+            let mut depth = 0;
+            let filled = matches!(kind.as_str(), "TABLE" | "MATERIALIZED VIEW")
+                && words.iter().any(|w| {
+                    match w.as_str() {
+                        "(" => depth += 1,
+                        ")" => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0 && w == "AS"
+                });
+            if filled {
+                format!("SELECT {}", rows)
+            } else {
+                format!("CREATE {}", kind)
+            }
+        }
+        "ALTER" | "DROP" if word(1) == "OWNED" => format!("{} OWNED", word(0)),
+        "ALTER" | "DROP" => format!("{} {}", word(0), object_type(&words[1..])),
+        "COMMIT" | "ROLLBACK" if word(1) == "PREPARED" => format!("{} PREPARED", word(0)),
+        "PREPARE" if word(1) == "TRANSACTION" => "PREPARE TRANSACTION".to_string(),
+        "START" => "START TRANSACTION".to_string(),
+        "END" => "COMMIT".to_string(),
+        "ABORT" => "ROLLBACK".to_string(),
+        "ANALYSE" => "ANALYZE".to_string(),
+        "TRUNCATE" => "TRUNCATE TABLE".to_string(),
+        "LOCK" => "LOCK TABLE".to_string(),
+        "REFRESH" => "REFRESH MATERIALIZED VIEW".to_string(),
+        "IMPORT" => "IMPORT FOREIGN SCHEMA".to_string(),
+        "SECURITY" => "SECURITY LABEL".to_string(),
+        "REASSIGN" => "REASSIGN OWNED".to_string(),
+        "DECLARE" => "DECLARE CURSOR".to_string(),
+        "CLOSE" if word(1) == "ALL" => "CLOSE CURSOR ALL".to_string(),
+        "CLOSE" => "CLOSE CURSOR".to_string(),
+        "SET" if word(1) == "CONSTRAINTS" => "SET CONSTRAINTS".to_string(),
+        "DISCARD" | "DEALLOCATE" if word(1) == "ALL" => format!("{} ALL", word(0)),
+        "DISCARD" => format!("DISCARD {}", word(1)),
+        keyword => keyword.to_string(),
+    }
+}
+
+/// The kind of object a CREATE, ALTER or DROP is about, as its tag names it, from the
+/// words that start with it.
+fn object_type(words: &[String]) -> String {
+    let word = |i: usize| words.get(i).map_or("", String::as_str);
+    match word(0) {
+        "MATERIALIZED" => "MATERIALIZED VIEW".to_string(),
+        "FOREIGN" if word(1) == "TABLE" => "FOREIGN TABLE".to_string(),
+        "FOREIGN" => "FOREIGN DATA WRAPPER".to_string(),
+        "TEXT" => format!("TEXT SEARCH {}", word(2)),
+        "EVENT" => "EVENT TRIGGER".to_string(),
+        "ACCESS" => "ACCESS METHOD".to_string(),
+        "DEFAULT" => "DEFAULT PRIVILEGES".to_string(),
+        "USER" if word(1) == "MAPPING" => "USER MAPPING".to_string(),
+        // Users and groups are roles to the server
+        "USER" | "GROUP" => "ROLE".to_string(),
+        "OPERATOR" if matches!(word(1), "CLASS" | "FAMILY") => format!("OPERATOR {}", word(1)),
+        kind => kind.to_string(),
+    }
+}
+
+/// A statement that destroys data or schema, caught before it runs.
+#[derive(Debug, Clone)]
+pub struct Destructive {
+    /// What makes it destructive, e.g. `DELETE without WHERE`
+    pub reason: &'static str,
+    /// A `SELECT COUNT(*)` over the rows it would touch, when one can be derived
+    pub count_sql: Option<String>,
+}
+
+/// Checks for `DELETE` or `UPDATE` without a `WHERE` clause, `DROP`, `TRUNCATE` or
+/// `ALTER`, looking past a leading `WITH` clause. A `WHERE` inside parentheses, such as
+/// in a subquery, doesn't count. `EXPLAIN ANALYZE` is judged by the statement it runs.
+pub fn destructive(sql: &str) -> Option<Destructive> {
+    if let Some(statement) = analyzed_statement(sql) {
+        return destructive(statement);
+    }
+
+    // Top-level words and punctuation with their byte ranges
+    let mut words: Vec<(String, Range<usize>)> = Vec::new();
+    let mut depth = 0usize;
+    let mut offset = 0;
+    for token in tokenize(sql) {
+        let start = offset;
+        offset += token.text.len();
+        match token.token_type {
+            TokenType::Whitespace | TokenType::Comment => {}
+            TokenType::Operator if token.text == "(" => depth += 1,
+            TokenType::Operator if token.text == ")" => depth = depth.saturating_sub(1),
+            _ if depth == 0 => words.push((token.text.to_uppercase(), start..offset)),
+            _ => {}
+        }
+    }
+
+    let verb = if words.first()?.0 == "WITH" {
+        words.iter().position(|(w, _)| {
+            matches!(
+                w.as_str(),
+                "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "VALUES"
+            )
+        })?
+    } else {
+        0
+    };
+    let rest = &words[verb + 1..];
+    let has = |word: &str| rest.iter().any(|(w, _)| w == word);
+    // Keeps a leading WITH clause in front of the rewritten query
+    let prefix = &sql[..words[verb].1.start];
+
+    match words[verb].0.as_str() {
+        "DELETE" if !has("WHERE") => {
+            let end = rest
+                .iter()
+                .find(|(w, _)| w == "RETURNING")
+                .map_or(sql.len(), |(_, r)| r.start);
+            Some(Destructive {
+                reason: "DELETE without WHERE",
+                count_sql: Some(format!(
+                    "{}SELECT COUNT(*) {}",
+                    prefix,
+                    sql[words[verb].1.end..end].trim()
+                )),
+            })
+        }
+        "UPDATE" if !has("WHERE") => {
+            // Skip SQLite's `UPDATE OR <conflict>`
+            let skip = if rest.first().is_some_and(|(w, _)| w == "OR") {
+                2
+            } else {
+                0
+            };
+            let table = rest.get(skip)?.1.start;
+            let count_sql = rest.iter().find(|(w, _)| w == "SET").map(|(_, set)| {
+                format!(
+                    "{}SELECT COUNT(*) FROM {}",
+                    prefix,
+                    sql[table..set.start].trim()
+                )
+            });
+            Some(Destructive {
+                reason: "UPDATE without WHERE",
+                count_sql,
+            })
+        }
+        "TRUNCATE" => {
+            let from = rest
+                .iter()
+                .position(|(w, _)| !matches!(w.as_str(), "TABLE" | "ONLY"))?;
+            Some(Destructive {
+                reason: "TRUNCATE",
+                count_sql: count_table(sql, &rest[from..]),
+            })
+        }
+        "DROP" => {
+            let count_sql = if rest.first().is_some_and(|(w, _)| w == "TABLE") {
+                let from = if has("EXISTS") { 3 } else { 1 };
+                rest.get(from..).and_then(|words| count_table(sql, words))
+            } else {
+                None
+            };
+            Some(Destructive {
+                reason: "DROP",
+                count_sql,
+            })
+        }
+        "ALTER" => Some(Destructive {
+            reason: "ALTER",
+            count_sql: None,
+        }),
+        _ => None,
+    }
+}
+
+/// The statement an `EXPLAIN ANALYZE` executes. Plain `EXPLAIN` only plans it, so it
+/// gives none.
+fn analyzed_statement(sql: &str) -> Option<&str> {
+    let tokens = significant_tokens(sql);
+    if !tokens.first()?.0.eq_ignore_ascii_case("EXPLAIN") {
+        return None;
+    }
+    let mut analyze = false;
+    let mut i = 1;
+    // Postgres options in parentheses, e.g. `(ANALYZE, BUFFERS)` or `(ANALYZE off)`
+    if tokens.get(1).is_some_and(|(t, _)| t == "(") {
+        let close = tokens.iter().position(|(t, _)| t == ")")?;
+        let options = &tokens[2..close];
+        for (j, (option, _)) in options.iter().enumerate() {
+            if matches!(option.to_uppercase().as_str(), "ANALYZE" | "ANALYSE") {
+                analyze = !options.get(j + 1).is_some_and(|(value, _)| {
+                    matches!(value.to_uppercase().as_str(), "FALSE" | "OFF" | "0")
+                });
+            }
+        }
+        i = close + 1;
+    }
+    // Options in front of the statement, such as MySQL's `FORMAT=JSON`
+    while let Some((word, _)) = tokens.get(i) {
+        match word.to_uppercase().as_str() {
+            "ANALYZE" | "ANALYSE" => analyze = true,
+            "VERBOSE" | "FORMAT" | "=" | "JSON" | "TREE" | "TRADITIONAL" | "QUERY" | "PLAN" => {}
+            _ => break,
+        }
+        i += 1;
+    }
+    let (_, statement) = tokens.get(i)?;
+    analyze.then(|| &sql[statement.start..])
+}
+
+/// Counts the rows of the single, possibly schema-qualified, table `words` start with.
+fn count_table(sql: &str, words: &[(String, Range<usize>)]) -> Option<String> {
+    let (_, first) = words.first()?;
+    let mut end = 1;
+    while words.get(end).is_some_and(|(w, _)| w == ".") && end + 1 < words.len() {
+        end += 2;
+    }
+    // Several tables at once are left uncounted
+    if words.get(end).is_some_and(|(w, _)| w == ",") {
+        return None;
+    }
+    let name = &sql[first.start..words[end - 1].1.end];
+    Some(format!("SELECT COUNT(*) FROM {}", name))
+}
+
+/// `CHECK` constraints in a `CREATE TABLE` statement, each with its name if it has one.
+pub fn check_constraints(create_sql: &str) -> Vec<(Option<String>, String)> {
+    let words = significant_tokens(create_sql);
+    let mut constraints = Vec::new();
+    for (i, (word, range)) in words.iter().enumerate() {
+        if !word.eq_ignore_ascii_case("CHECK") || words.get(i + 1).is_none_or(|(w, _)| w != "(") {
+            continue;
+        }
+        let mut depth = 0;
+        let Some(end) = words[i + 1..].iter().find_map(|(w, r)| {
+            match w.as_str() {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(r.end)
+        }) else {
+            continue;
+        };
+        let name = i
+            .checked_sub(2)
+            .filter(|&n| words[n].0.eq_ignore_ascii_case("CONSTRAINT"))
+            .map(|n| unquote(&words[n + 1].0));
+        constraints.push((name, create_sql[range.start..end].to_string()));
+    }
+    constraints
+}
+
+/// When a `CREATE TRIGGER` statement fires, e.g. `AFTER UPDATE OF name`: the words
+/// between the trigger's name and `ON`.
+pub fn trigger_timing(create_sql: &str) -> String {
+    let words = significant_tokens(create_sql);
+    let Some(trigger) = words
+        .iter()
+        .position(|(w, _)| w.eq_ignore_ascii_case("TRIGGER"))
+    else {
+        return String::new();
+    };
+    let mut name = trigger + 1;
+    if words
+        .get(name)
+        .is_some_and(|(w, _)| w.eq_ignore_ascii_case("IF"))
+    {
+        name += 3;
+    }
+    // A schema-qualified name
+    if words.get(name + 1).is_some_and(|(w, _)| w == ".") {
+        name += 2;
+    }
+    words
+        .iter()
+        .skip(name + 1)
+        .take_while(|(w, _)| !w.eq_ignore_ascii_case("ON"))
+        .map(|(w, _)| w.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What a `CREATE INDEX` statement says about the index.
+#[derive(Debug, Clone, Default)]
+pub struct IndexDefinition {
+    pub is_unique: bool,
+    /// Indexed columns and expressions as written, in order
+    pub columns: Vec<String>,
+    /// The `WHERE` clause of a partial index
+    pub predicate: Option<String>,
+}
+
+pub fn parse_create_index(create_sql: &str) -> IndexDefinition {
+    let words = significant_tokens(create_sql);
+    let is_unique = words
+        .get(1)
+        .is_some_and(|(w, _)| w.eq_ignore_ascii_case("UNIQUE"));
+    let Some(open) = words
+        .iter()
+        .position(|(w, _)| w.eq_ignore_ascii_case("ON"))
+        .and_then(|on| {
+            words[on..]
+                .iter()
+                .position(|(w, _)| w == "(")
+                .map(|p| on + p)
+        })
+    else {
+        return IndexDefinition {
+            is_unique,
+            ..Default::default()
+        };
+    };
+
+    // Split the key list at commas between its outer parentheses
+    let mut columns = Vec::new();
+    let mut depth = 0;
+    let mut start = words[open].1.end;
+    let mut close = words.len();
+    for (i, (word, range)) in words.iter().enumerate().skip(open) {
+        match word.as_str() {
+            "(" => depth += 1,
+            ")" | "," if depth == 1 => {
+                columns.push(create_sql[start..range.start].trim().to_string());
+                start = range.end;
+                if word == ")" {
+                    close = i;
+                    break;
+                }
+            }
+            ")" => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let predicate = words
+        .get(close + 1)
+        .filter(|(w, _)| w.eq_ignore_ascii_case("WHERE"))
+        .map(|(_, range)| create_sql[range.end..].trim().to_string());
+    IndexDefinition {
+        is_unique,
+        columns,
+        predicate,
+    }
+}
+
+/// Tokens other than whitespace and comments, with their byte ranges.
+fn significant_tokens(sql: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    for token in tokenize(sql) {
+        let start = offset;
+        offset += token.text.len();
+        if !matches!(token.token_type, TokenType::Whitespace | TokenType::Comment) {
+            tokens.push((token.text, start..offset));
+        }
+    }
+    tokens
+}
+
+/// Strips the quotes from a quoted identifier.
+fn unquote(name: &str) -> String {
+    match name.chars().next() {
+        Some('"') => name.trim_matches('"').replace("\"\"", "\""),
+        Some('`' | '[') => name.trim_matches(['`', '[', ']']).to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// Every name a statement mentions, unquoted and lower-cased, for matching against
+/// tables in the schema.
+pub fn mentioned_names(sql: &str) -> HashSet<String> {
+    tokenize(sql)
+        .into_iter()
+        .filter_map(|t| match t.token_type {
+            TokenType::String if t.text.starts_with('"') => Some(unquote(&t.text)),
+            TokenType::Identifier | TokenType::Keyword | TokenType::Function | TokenType::Type => {
+                Some(unquote(&t.text))
+            }
+            _ => None,
+        })
+        .map(|name| name.to_lowercase())
+        .collect()
+}
+
+/// Double-quotes an identifier for use in SQL.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Single-quotes a string literal for use in SQL.
+pub fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// A quoted name, prefixed with its quoted schema if it has one.
+pub fn qualified_name(schema: Option<&str>, name: &str) -> String {
+    match schema {
+        Some(schema) => format!("{}.{}", quote_identifier(schema), quote_identifier(name)),
+        None => quote_identifier(name),
+    }
+}
+
+fn is_trigger(words: &[String]) -> bool {
+    words.first().is_some_and(|w| w == "CREATE") && words.iter().any(|w| w == "TRIGGER")
+}
+
+fn push_statement(
+    statements: &mut Vec<Statement>,
+    sql: &str,
+    start: usize,
+    end: usize,
+    words: &[String],
+) {
+    if words.is_empty() {
+        return;
+    }
+    let text = &sql[start..end];
+    let trimmed_start = start + (text.len() - text.trim_start().len());
+    let trimmed_end = start + text.trim_end().len();
+    statements.push(Statement {
+        text: sql[trimmed_start..trimmed_end].to_string(),
+        start: trimmed_start,
+        end: trimmed_end,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(sql: &str) -> Vec<String> {
+        split_statements(sql, false)
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    }
+
+    #[test]
+    fn splits_at_top_level_semicolons() {
+        assert_eq!(texts("SELECT 1; SELECT 2;\n"), ["SELECT 1", "SELECT 2"]);
+        assert_eq!(texts("SELECT 1"), ["SELECT 1"]);
+        assert!(texts("  ;; ").is_empty());
+    }
+
+    #[test]
+    fn ignores_semicolons_in_comments() {
+        assert_eq!(
+            texts("SELECT 1; -- one; two\nSELECT /* ; */ 2"),
+            ["SELECT 1", "-- one; two\nSELECT /* ; */ 2"]
+        );
+        assert_eq!(texts("SELECT 1; -- done;"), ["SELECT 1"]);
+    }
+
+    #[test]
+    fn ignores_semicolons_in_quotes() {
+        assert_eq!(
+            texts(r#"SELECT 'a;b', 'it''s;'; SELECT "x;y" FROM t"#),
+            [r#"SELECT 'a;b', 'it''s;'"#, r#"SELECT "x;y" FROM t"#]
+        );
+    }
+
+    #[test]
+    fn ignores_semicolons_in_dollar_quotes() {
+        let function = "CREATE FUNCTION f() RETURNS int AS $body$ BEGIN RETURN 1; END; $body$ \
+                        LANGUAGE plpgsql";
+        assert_eq!(
+            texts(&format!("{}; SELECT f()", function)),
+            [function, "SELECT f()"]
+        );
+        assert_eq!(texts("DO $$ BEGIN NULL; END $$; SELECT 1").len(), 2);
+    }
+
+    #[test]
+    fn keeps_sqlite_trigger_bodies_whole() {
+        let trigger = "CREATE TRIGGER t AFTER INSERT ON a BEGIN \
+                       UPDATE b SET n = CASE WHEN n > 0 THEN n END; DELETE FROM c; END";
+        assert_eq!(
+            texts(&format!("{}; SELECT 1", trigger)),
+            [trigger, "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn handles_backslash_escapes() {
+        // Postgres escape strings always take backslashes
+        assert_eq!(
+            texts(r"SELECT E'it\'s; here'; SELECT 2"),
+            [r"SELECT E'it\'s; here'", "SELECT 2"]
+        );
+        // Plain strings only do on MySQL
+        let mysql: Vec<String> = split_statements(r"SELECT 'it\'s; here'; SELECT 2", true)
+            .into_iter()
+            .map(|s| s.text)
+            .collect();
+        assert_eq!(mysql, [r"SELECT 'it\'s; here'", "SELECT 2"]);
+        assert_eq!(
+            texts(r"SELECT 'C:\'; SELECT 2"),
+            [r"SELECT 'C:\'", "SELECT 2"]
+        );
+    }
+
+    fn reason(sql: &str) -> Option<&'static str> {
+        destructive(sql).map(|d| d.reason)
+    }
+
+    fn count_sql(sql: &str) -> Option<String> {
+        destructive(sql).and_then(|d| d.count_sql)
+    }
+
+    #[test]
+    fn flags_changes_without_where() {
+        assert_eq!(reason("DELETE FROM t"), Some("DELETE without WHERE"));
+        assert_eq!(reason("delete from t where id = 1"), None);
+        assert_eq!(reason("UPDATE t SET a = 1"), Some("UPDATE without WHERE"));
+        assert_eq!(reason("UPDATE t SET a = 1 WHERE id = 2"), None);
+        assert_eq!(reason("SELECT * FROM t"), None);
+        assert_eq!(
+            reason("-- WHERE\nDELETE FROM t"),
+            Some("DELETE without WHERE")
+        );
+    }
+
+    #[test]
+    fn ignores_where_in_subqueries() {
+        assert_eq!(
+            reason("DELETE FROM t WHERE id IN (SELECT id FROM u WHERE x)"),
+            None
+        );
+        assert_eq!(
+            reason("UPDATE t SET a = (SELECT max(a) FROM u WHERE u.id = 1)"),
+            Some("UPDATE without WHERE")
+        );
+        assert_eq!(
+            reason("DELETE FROM t RETURNING (SELECT 1 WHERE true)"),
+            Some("DELETE without WHERE")
+        );
+    }
+
+    #[test]
+    fn looks_past_with_clauses() {
+        let sql = "WITH old AS (SELECT id FROM t WHERE a < 0) DELETE FROM t";
+        assert_eq!(reason(sql), Some("DELETE without WHERE"));
+        assert_eq!(
+            count_sql(sql).as_deref(),
+            Some("WITH old AS (SELECT id FROM t WHERE a < 0) SELECT COUNT(*) FROM t")
+        );
+        assert_eq!(
+            reason("WITH old AS (SELECT 1) DELETE FROM t WHERE id IN (SELECT * FROM old)"),
+            None
+        );
+        assert_eq!(reason("WITH x AS (DELETE FROM t) SELECT 1"), None);
+    }
+
+    #[test]
+    fn judges_explain_analyze_by_its_statement() {
+        assert_eq!(reason("EXPLAIN DELETE FROM t"), None);
+        assert_eq!(
+            reason("EXPLAIN ANALYZE DELETE FROM t"),
+            Some("DELETE without WHERE")
+        );
+        assert_eq!(
+            count_sql("EXPLAIN ANALYZE DELETE FROM t").as_deref(),
+            Some("SELECT COUNT(*) FROM t")
+        );
+        assert_eq!(
+            reason("EXPLAIN (ANALYZE, BUFFERS) UPDATE t SET a = 1"),
+            Some("UPDATE without WHERE")
+        );
+        assert_eq!(reason("EXPLAIN (ANALYZE off) DELETE FROM t"), None);
+        assert_eq!(reason("EXPLAIN ANALYZE DELETE FROM t WHERE id = 1"), None);
+        assert_eq!(reason("EXPLAIN QUERY PLAN DELETE FROM t"), None);
+    }
+
+    #[test]
+    fn flags_schema_changes() {
+        assert_eq!(reason("DROP TABLE IF EXISTS s.t"), Some("DROP"));
+        assert_eq!(
+            count_sql("DROP TABLE IF EXISTS s.t").as_deref(),
+            Some("SELECT COUNT(*) FROM s.t")
+        );
+        assert_eq!(count_sql("DROP INDEX i"), None);
+        assert_eq!(
+            count_sql("TRUNCATE TABLE ONLY t").as_deref(),
+            Some("SELECT COUNT(*) FROM t")
+        );
+        // Several tables at once are left uncounted
+        assert_eq!(count_sql("TRUNCATE a, b"), None);
+        assert_eq!(reason("ALTER TABLE t ADD c int"), Some("ALTER"));
+        assert_eq!(
+            count_sql("UPDATE OR IGNORE t SET a = 1").as_deref(),
+            Some("SELECT COUNT(*) FROM t")
+        );
+    }
+
+    #[test]
+    fn finds_the_statement_at_the_cursor() {
+        let sql = "SELECT 1;\nSELECT 2\n\nSELECT 3";
+        let at = |offset| statement_at(sql, offset, false).map(|s| s.text);
+        assert_eq!(at(0).as_deref(), Some("SELECT 1"));
+        assert_eq!(at(12).as_deref(), Some("SELECT 2"));
+        // Blank lines end a statement too
+        assert_eq!(at(sql.len()).as_deref(), Some("SELECT 3"));
+    }
+
+    #[test]
+    fn names_commands_as_postgres_tags_them() {
+        assert_eq!(
+            command_tag("INSERT INTO t VALUES (1), (2)", 2),
+            "INSERT 0 2"
+        );
+        assert_eq!(
+            command_tag("WITH x AS (SELECT 1) UPDATE t SET a = 1", 3),
+            "UPDATE 3"
+        );
+        assert_eq!(
+            command_tag("CREATE UNIQUE INDEX i ON t (a)", 0),
+            "CREATE INDEX"
+        );
+        assert_eq!(
+            command_tag("create or replace view v as select 1", 0),
+            "CREATE VIEW"
+        );
+        assert_eq!(
+            command_tag("CREATE TABLE t (a int GENERATED ALWAYS AS (1) STORED)", 0),
+            "CREATE TABLE"
+        );
+        assert_eq!(
+            command_tag("CREATE TABLE t2 AS SELECT * FROM t", 5),
+            "SELECT 5"
+        );
+        assert_eq!(command_tag("CREATE USER alice", 0), "CREATE ROLE");
+        assert_eq!(
+            command_tag("DROP MATERIALIZED VIEW mv", 0),
+            "DROP MATERIALIZED VIEW"
+        );
+        assert_eq!(command_tag("ALTER TABLE t ADD c int", 0), "ALTER TABLE");
+        assert_eq!(command_tag("GRANT SELECT ON t TO alice", 0), "GRANT");
+        assert_eq!(command_tag("TRUNCATE t", 0), "TRUNCATE TABLE");
+        assert_eq!(command_tag("COPY t TO '/tmp/t.csv'", 7), "COPY 7");
+        assert_eq!(command_tag("END", 0), "COMMIT");
+    }
+}
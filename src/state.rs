@@ -1,5 +1,6 @@
+use crate::libpq::{is_key_value, strip_password};
 use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 
@@ -15,6 +16,21 @@ pub struct SavedTab {
     pub position: i32,
 }
 
+/// Preferences remembered for one connection.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionSettings {
+    /// Ask before running DELETE/UPDATE without WHERE, DROP, TRUNCATE or ALTER
+    pub confirm_destructive: bool,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        Self {
+            confirm_destructive: true,
+        }
+    }
+}
+
 impl StateStore {
     pub fn open() -> Result<Self> {
         let state_path = Self::state_db_path()?;
@@ -47,6 +63,17 @@ impl StateStore {
             );
 
             CREATE INDEX IF NOT EXISTS idx_tabs_session ON tabs(session_id);
+
+            CREATE TABLE IF NOT EXISTS connection_settings (
+                db_path TEXT PRIMARY KEY,
+                confirm_destructive INTEGER NOT NULL DEFAULT 1
+            );
+
+            CREATE TABLE IF NOT EXISTS recent_connections (
+                db_path TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                opened_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
             ",
         )?;
 
@@ -63,6 +90,7 @@ impl StateStore {
     }
 
     pub fn load_session(&self, db_path: &str) -> Result<Option<(Vec<SavedTab>, usize)>> {
+        self.migrate_legacy_key(db_path)?;
         let canonical_path = Self::session_key(db_path);
 
         let mut stmt = self
@@ -136,8 +164,81 @@ impl StateStore {
         Ok(())
     }
 
+    pub fn load_settings(&self, db_path: &str) -> Result<ConnectionSettings> {
+        self.migrate_legacy_key(db_path)?;
+        let settings = self
+            .conn
+            .query_row(
+                "SELECT confirm_destructive FROM connection_settings WHERE db_path = ?",
+                [Self::session_key(db_path)],
+                |row| {
+                    Ok(ConnectionSettings {
+                        confirm_destructive: row.get(0)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(settings.unwrap_or_default())
+    }
+
+    pub fn save_settings(&self, db_path: &str, settings: &ConnectionSettings) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO connection_settings (db_path, confirm_destructive) VALUES (?, ?)
+             ON CONFLICT(db_path) DO UPDATE SET
+                confirm_destructive = excluded.confirm_destructive",
+            params![Self::session_key(db_path), settings.confirm_destructive],
+        )?;
+        Ok(())
+    }
+
+    /// Remembers a connection for the startup picker, by a label that can be shown and
+    /// reopened but holds no password.
+    pub fn record_recent(&self, db_path: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO recent_connections (db_path, label, opened_at)
+             VALUES (?, ?, CURRENT_TIMESTAMP)
+             ON CONFLICT(db_path) DO UPDATE SET
+                label = excluded.label,
+                opened_at = CURRENT_TIMESTAMP",
+            params![Self::session_key(db_path), Self::normalize(db_path)],
+        )?;
+        Ok(())
+    }
+
+    /// Labels of the connections opened most recently, latest first.
+    pub fn recent_connections(&self, limit: usize) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT label FROM recent_connections ORDER BY opened_at DESC, rowid DESC LIMIT ?",
+        )?;
+        let labels = stmt
+            .query_map([limit as i64], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(labels)
+    }
+
+    /// Files by their canonical path, connection strings and profile names without any
+    /// password, so that a connection is known by the same name however it was given.
+    fn normalize(path: &str) -> String {
+        if path.starts_with('@') || path.contains("://") || is_key_value(path) {
+            strip_password(path)
+        } else {
+            Path::new(path)
+                .canonicalize()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.to_string())
+        }
+    }
+
     fn session_key(path: &str) -> String {
-        // For file paths, canonicalize first to normalize the path
+        // Hash the result so connection strings with passwords aren't stored in plaintext
+        let mut hasher = Sha256::new();
+        hasher.update(Self::normalize(path).as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// The key sessions and settings were saved under before passwords were stripped:
+    /// connection strings as given, and files by their canonical path.
+    fn legacy_session_key(path: &str) -> String {
         let normalized = if path.starts_with("postgres://")
             || path.starts_with("postgresql://")
             || path.contains("host=")
@@ -149,10 +250,27 @@ impl StateStore {
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|_| path.to_string())
         };
-
-        // Hash the result so connection strings with passwords aren't stored in plaintext
         let mut hasher = Sha256::new();
         hasher.update(normalized.as_bytes());
         format!("{:x}", hasher.finalize())
     }
+
+    /// Moves a session and settings saved under the legacy key to the current one, unless
+    /// the current key already has its own.
+    fn migrate_legacy_key(&self, db_path: &str) -> Result<()> {
+        let (legacy, current) = (
+            Self::legacy_session_key(db_path),
+            Self::session_key(db_path),
+        );
+        if legacy == current {
+            return Ok(());
+        }
+        for table in ["sessions", "connection_settings"] {
+            self.conn.execute(
+                &format!("UPDATE OR IGNORE {table} SET db_path = ? WHERE db_path = ?"),
+                params![current, legacy],
+            )?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,125 @@
+use crate::app::{App, Dialog, RowEstimate};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+
+pub fn render(frame: &mut Frame, app: &App, dialog: Dialog) {
+    let key = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let (title, lines) = match dialog {
+        Dialog::QuitWithTransaction => {
+            let connection = app.open_transaction().unwrap_or(app.connection());
+            let open = connection.transaction.unwrap_or_default();
+            // Say which connection when there is more than one
+            let on = if app.connections.len() > 1 {
+                format!(" on {}", connection.label())
+            } else {
+                String::new()
+            };
+            let mut lines = vec![
+                Line::from(format!(
+                    "A transaction is open{} with {} uncommitted statements.",
+                    on, open.statements
+                )),
+                Line::from(""),
+            ];
+            if open.failed {
+                lines.insert(
+                    1,
+                    Line::from(Span::styled(
+                        "It has failed, so committing will roll it back.",
+                        Style::default().fg(Color::Red),
+                    )),
+                );
+            }
+            lines.push(Line::from(vec![
+                Span::styled("c", key),
+                Span::raw(" Commit and quit   "),
+                Span::styled("r", key),
+                Span::raw(" Roll back and quit   "),
+                Span::styled("Esc", key),
+                Span::raw(" Stay"),
+            ]));
+            (" Quit ", lines)
+        }
+        Dialog::ConfirmDestructive => {
+            let guarded = app
+                .pending_run
+                .as_ref()
+                .map(|p| p.guarded.as_slice())
+                .unwrap_or_default();
+            let mut lines = Vec::new();
+            for statement in guarded {
+                let estimate = match statement.estimate {
+                    RowEstimate::Counting => " (counting rows…)".to_string(),
+                    RowEstimate::Rows(1) => " (about 1 row)".to_string(),
+                    RowEstimate::Rows(n) => format!(" (about {} rows)", n),
+                    RowEstimate::Unknown => String::new(),
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(statement.reason, Style::default().fg(Color::Red)),
+                    Span::styled(estimate, Style::default().fg(Color::DarkGray)),
+                ]));
+                // Long statements are cut to their first few lines
+                let text: Vec<&str> = statement.text.lines().collect();
+                for line in text.iter().take(4) {
+                    lines.push(Line::from(format!("  {}", line)));
+                }
+                if text.len() > 4 {
+                    lines.push(Line::from("  …"));
+                }
+                lines.push(Line::from(""));
+            }
+            if app.pending_run.as_ref().is_some_and(|p| p.confirmed) {
+                lines.push(Line::from(Span::styled(
+                    "Stopping the row count…",
+                    Style::default().fg(Color::Yellow),
+                )));
+            } else {
+                lines.push(Line::from(vec![
+                    Span::styled("y", key),
+                    Span::raw(" Run   "),
+                    Span::styled("n", key),
+                    Span::raw(" Cancel"),
+                ]));
+            }
+            lines.push(Line::from(Span::styled(
+                "F4 stops asking on this connection",
+                Style::default().fg(Color::DarkGray),
+            )));
+            (" Confirm ", lines)
+        }
+    };
+
+    let area = centered(frame.area(), 72, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn centered(r: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(r.width);
+    let height = height.min(r.height);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
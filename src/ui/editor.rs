@@ -5,6 +5,10 @@ use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
+use std::time::Duration;
+
+/// How long a statement run on its own stays highlighted.
+const FLASH_DURATION: Duration = Duration::from_millis(600);
 
 pub fn render_tabs(app: &App) -> Line<'static> {
     let mut spans = Vec::new();
@@ -21,7 +25,22 @@ pub fn render_tabs(app: &App) -> Line<'static> {
             Style::default().fg(Color::White)
         };
 
-        spans.push(Span::styled(format!(" {} ", tab.name), style));
+        let marker = if tab.is_running() { "● " } else { "" };
+        // Name the tab's connection once there is more than one
+        if app.connections.len() > 1 {
+            let connection = &app.connections[tab.connection];
+            let label_style = match connection.color {
+                Some(color) if !is_active => style.fg(color),
+                _ => style,
+            };
+            spans.push(Span::styled(format!(" {}{} ", marker, tab.name), style));
+            spans.push(Span::styled(
+                format!("· {} ", connection.label()),
+                label_style.add_modifier(Modifier::DIM),
+            ));
+        } else {
+            spans.push(Span::styled(format!(" {}{} ", marker, tab.name), style));
+        }
         spans.push(Span::raw(" "));
     }
 
@@ -46,7 +65,16 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     let mut lines: Vec<Line> = Vec::new();
     let line_num_width = tab.content.len().to_string().len().max(2);
 
+    let flash = tab
+        .flash
+        .as_ref()
+        .filter(|(_, started)| started.elapsed() < FLASH_DURATION)
+        .map(|(range, _)| range.clone());
+    let mut line_start = 0;
+
     for (i, line_content) in tab.content.iter().enumerate() {
+        let line_offset = line_start;
+        line_start += line_content.len() + 1;
         if i < tab.scroll_offset {
             continue;
         }
@@ -58,7 +86,20 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
         let mut spans = vec![Span::styled(line_num, Style::default().fg(Color::DarkGray))];
 
         // Apply syntax highlighting
-        let highlighted = highlighter.highlight_line(line_content);
+        let mut highlighted = highlighter.highlight_line(line_content);
+        if let Some(range) = &flash {
+            let from = range
+                .start
+                .saturating_sub(line_offset)
+                .min(line_content.len());
+            let to = range
+                .end
+                .saturating_sub(line_offset)
+                .min(line_content.len());
+            if from < to {
+                highlighted.spans = flash_spans(highlighted.spans, from, to);
+            }
+        }
 
         // If this is the cursor line and editor is focused, we need to handle cursor
         if i == tab.cursor_line && is_focused {
@@ -140,3 +181,32 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     let paragraph = Paragraph::new(lines);
     frame.render_widget(paragraph, area);
 }
+
+/// Gives bytes `from..to` of a highlighted line a background, splitting spans as needed.
+fn flash_spans(spans: Vec<Span<'static>>, from: usize, to: usize) -> Vec<Span<'static>> {
+    let flash = Style::default().bg(Color::DarkGray);
+    let mut out = Vec::new();
+    let mut col = 0;
+    for span in spans {
+        let text = span.content.to_string();
+        let end = col + text.len();
+        let a = from.clamp(col, end) - col;
+        let b = to.clamp(col, end) - col;
+        for (part, flashed) in [
+            (&text[..a], false),
+            (&text[a..b], true),
+            (&text[b..], false),
+        ] {
+            if !part.is_empty() {
+                let style = if flashed {
+                    span.style.patch(flash)
+                } else {
+                    span.style
+                };
+                out.push(Span::styled(part.to_string(), style));
+            }
+        }
+        col = end;
+    }
+    out
+}
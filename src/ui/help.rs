@@ -27,23 +27,35 @@ pub fn render(frame: &mut Frame, _app: &App) {
     let shortcuts: Vec<(&str, &str, bool)> = vec![
         ("", "Global", true),
         ("F5 / Ctrl+R / Ctrl+Enter", "Execute query", false),
+        ("F6 / Alt+Enter", "Execute statement under cursor", false),
+        ("F4", "Toggle confirming destructive statements", false),
+        ("F7", "Toggle transaction mode", false),
+        ("F8 / F9", "Commit / roll back transaction", false),
         ("F2", "New query tab", false),
         ("Ctrl+W", "Close current tab", false),
         ("Alt+← / Alt+→", "Switch tabs", false),
+        ("Ctrl+O", "Open or switch to a connection", false),
+        ("F3", "Reconnect the current tab's connection", false),
         ("Tab / Shift+Tab", "Switch panels", false),
         ("Ctrl+PgUp / Ctrl+PgDn", "Previous/Next result page", false),
         ("? / F1", "Toggle help", false),
         ("Esc", "Close help / Cancel", false),
-        ("q / Ctrl+C", "Quit", false),
+        ("Ctrl+C", "Cancel running query, or quit", false),
+        ("q", "Quit", false),
         ("", "", false),
         ("", "Schema Browser (Left Panel)", true),
         ("↑ / ↓ / j / k", "Navigate items", false),
-        ("Enter / →", "Expand or generate SELECT query", false),
+        (
+            "Enter / →",
+            "Expand table/group or generate SELECT query",
+            false,
+        ),
         ("← / Space", "Collapse item", false),
         ("s", "Generate SELECT * query", false),
         ("c", "Generate COUNT(*) query", false),
-        ("d", "Generate schema query (PRAGMA)", false),
+        ("d", "Open the CREATE statement or definition", false),
         ("r", "Refresh schema", false),
+        (".", "Show/hide system schemas (PostgreSQL)", false),
         ("", "", false),
         ("", "SQL Editor", true),
         ("Arrow keys", "Move cursor", false),
@@ -57,6 +69,9 @@ pub fn render(frame: &mut Frame, _app: &App) {
         ("← / → / h / l", "Navigate columns", false),
         ("Enter", "View cell detail", false),
         ("v", "View entire row as JSON", false),
+        ("f", "Open the row a foreign key points at", false),
+        ("r", "Open rows referencing the selected row", false),
+        ("[ / ]", "Previous/next statement result", false),
         ("PgUp / PgDn", "Scroll by 10 rows", false),
         ("Home / End", "Go to first/last row", false),
     ];
@@ -1,11 +1,11 @@
 use crate::app::{App, Panel};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph};
 
-use super::{editor, help, results, sidebar};
+use super::{dialog, editor, help, results, sidebar};
 
 pub fn render(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
@@ -28,28 +28,82 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     if app.show_help {
         help::render(frame, app);
     }
+
+    if let Some(kind) = app.dialog {
+        dialog::render(frame, app, kind);
+    }
 }
 
 fn render_title_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let db_name = std::path::Path::new(&app.db.path())
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| app.db.path());
+    let connection = app.connection();
+    let db_name = connection.label();
 
-    let title = Line::from(vec![
+    let mut spans = vec![
         Span::styled(" SQLClix ", Style::default().fg(Color::Cyan)),
         Span::styled("─ ", Style::default().fg(Color::DarkGray)),
-        Span::styled(db_name, Style::default().fg(Color::White)),
-        Span::styled(
-            format!(
-                "{:>width$}",
-                "[?] Help ",
-                width =
-                    area.width as usize - 12 - app.db.path().len().min(area.width as usize - 20)
+        match connection.color {
+            Some(color) => Span::styled(
+                format!(" {} ", db_name),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(color)
+                    .add_modifier(Modifier::BOLD),
             ),
-            Style::default().fg(Color::DarkGray),
+            None => Span::styled(db_name, Style::default().fg(Color::White)),
+        },
+        Span::raw(" "),
+    ];
+    if connection.lost {
+        spans.push(Span::styled(
+            " DISCONNECTED · F3 ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    if connection.read_only {
+        spans.push(Span::styled(
+            " RO ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    match connection.transaction {
+        Some(open) => {
+            let (label, bg) = if open.failed {
+                ("TXN FAILED", Color::Red)
+            } else {
+                ("TXN OPEN", Color::Yellow)
+            };
+            let plural = if open.statements == 1 { "" } else { "s" };
+            spans.push(Span::styled(
+                format!(" {} ({} statement{}) ", label, open.statements, plural),
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(bg)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        None if connection.transaction_mode => {
+            spans.push(Span::styled(" TXN ", Style::default().fg(Color::Yellow)));
+        }
+        None => {}
+    }
+    let used: usize = spans.iter().map(|s| s.width()).sum();
+    spans.push(Span::styled(
+        format!(
+            "{:>width$}",
+            "[?] Help ",
+            width = (area.width as usize).saturating_sub(used)
         ),
-    ]);
+        Style::default().fg(Color::DarkGray),
+    ));
+    let title = Line::from(spans);
 
     frame.render_widget(
         Paragraph::new(title).style(Style::default().bg(Color::Black)),
@@ -66,12 +120,17 @@ fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
         ])
         .split(area);
 
-    // Sidebar
+    // Sidebar, showing the active tab's connection
+    let sidebar_title = if app.connections.len() > 1 {
+        format!(" Schema ─ {} ", app.connection().label())
+    } else {
+        " Schema ".to_string()
+    };
     let sidebar_block = Block::default()
         .borders(Borders::ALL)
         .border_style(border_style(app.focus == Panel::Sidebar))
         .title(Span::styled(
-            " Schema ",
+            sidebar_title,
             Style::default().fg(if app.focus == Panel::Sidebar {
                 Color::Cyan
             } else {
@@ -81,7 +140,7 @@ fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let sidebar_inner = sidebar_block.inner(main_chunks[0]);
     frame.render_widget(sidebar_block, main_chunks[0]);
-    sidebar::render(frame, app, sidebar_inner);
+    sidebar::render(frame, app.connection_mut(), sidebar_inner);
 
     // Right side: Editor + Results
     let right_chunks = Layout::default()
@@ -103,14 +162,25 @@ fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
     editor::render(frame, app, editor_inner);
 
     // Results
-    let result_title = match &app.result {
+    let tab = app.current_tab();
+    let result_title = match app.result() {
+        None if tab.is_running() => " Results (running) ".to_string(),
+        _ if tab.is_running() && tab.is_streaming() => " Results (running) ".to_string(),
         Some(r) if r.error.is_some() => " Error ".to_string(),
-        Some(r) => format!(
-            " Results ({} rows) ─ Page {}/{} ",
-            r.row_count,
-            app.result_page + 1,
-            app.result_page_count()
-        ),
+        Some(r) => {
+            // More rows are fetched on demand while the cursor is open
+            let count = match (r.has_more, tab.is_streaming()) {
+                (false, _) => format!("{} rows", r.row_count),
+                (true, true) => format!("{}+ rows", r.row_count),
+                (true, false) => format!("{} rows, more available", r.row_count),
+            };
+            format!(
+                " Results ({}) ─ Page {}/{} ",
+                count,
+                app.result_page + 1,
+                app.result_page_count()
+            )
+        }
         None => " Results ".to_string(),
     };
 
@@ -136,13 +206,39 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         Panel::Sidebar => {
             "↑↓:Navigate  Enter:Select/Expand  s:SELECT  c:COUNT  d:Schema  r:Refresh"
         }
-        Panel::Editor => "F5:Run  F2:New Tab  Ctrl+W:Close  Alt+←→:Switch Tab",
-        Panel::Results => "↑↓←→:Navigate  Enter:View  v:Row  PgUp/Dn:Scroll  Home/End:Jump",
+        Panel::Editor => "F5:Run  F6:Run Current  F2:New Tab  Ctrl+O:Connect  Alt+←→:Switch Tab",
+        Panel::Results => {
+            "↑↓←→:Navigate  Enter:View  v:Row  f:Follow FK  r:Referencing  PgUp/Dn:Scroll"
+        }
     };
 
-    let time_info = match &app.result {
-        Some(r) => format!(" {:?} ", r.execution_time),
-        None => String::new(),
+    let time_info = if let Some(msg) = &app.status_message {
+        format!(" {} ", msg)
+    } else if let Some((tab_name, query)) = app.running_query() {
+        let action = if query.is_cancelled() {
+            "Cancelling"
+        } else {
+            "Ctrl+C:Cancel  Running"
+        };
+        let progress = match query.statement_count {
+            1 => String::new(),
+            n => format!(" {}/{}", query.current_statement() + 1, n),
+        };
+        format!(
+            " {}  ⏳ {}{} {:.1}s ",
+            action,
+            tab_name,
+            progress,
+            query.started.elapsed().as_secs_f64()
+        )
+    } else {
+        match app.result() {
+            Some(r) => match r.summary() {
+                Some(summary) => format!(" {}  {:?} ", summary, r.execution_time),
+                None => format!(" {:?} ", r.execution_time),
+            },
+            None => String::new(),
+        }
     };
 
     let status = Line::from(vec![
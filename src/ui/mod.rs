@@ -1,7 +1,10 @@
+mod dialog;
 mod editor;
 mod help;
 mod layout;
+mod picker;
 pub mod results;
 mod sidebar;
 
 pub use layout::render;
+pub use picker::render as render_picker;
@@ -0,0 +1,127 @@
+use crate::picker::{EntryKind, Picker};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+pub fn render(frame: &mut Frame, picker: &Picker) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Title bar
+            Constraint::Min(5),    // Entries
+            Constraint::Length(1), // Status bar
+        ])
+        .split(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(" SQLClix ", Style::default().fg(Color::Cyan)),
+            Span::styled("─ ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Open a connection", Style::default().fg(Color::White)),
+        ]))
+        .style(Style::default().bg(Color::Black)),
+        chunks[0],
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(Span::styled(" Connect ", Style::default().fg(Color::Cyan)));
+    let inner = block.inner(chunks[1]);
+    frame.render_widget(block, chunks[1]);
+
+    let inner_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Cyan)),
+            Span::raw(picker.input.clone()),
+            Span::styled("█", Style::default().fg(Color::Gray)),
+        ])),
+        inner_chunks[0],
+    );
+
+    let mut lines = Vec::new();
+
+    let selected_style = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut selected_line = 0;
+    let mut current_kind = None;
+    for (idx, entry) in picker.visible.iter().enumerate() {
+        if current_kind != Some(entry.kind) {
+            if current_kind.is_some() {
+                lines.push(Line::from(""));
+            }
+            current_kind = Some(entry.kind);
+            let header = match entry.kind {
+                EntryKind::Recent => "RECENT",
+                EntryKind::Profile => "PROFILES",
+                EntryKind::New => "NEW CONNECTION",
+            };
+            lines.push(Line::from(Span::styled(
+                header,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+
+        let label = match entry.kind {
+            EntryKind::New => format!("  Open {}", entry.target),
+            _ => format!("  {}", entry.target),
+        };
+        let mut spans = if idx == picker.selected {
+            selected_line = lines.len();
+            vec![Span::styled(label, selected_style)]
+        } else {
+            vec![Span::styled(label, Style::default().fg(Color::White))]
+        };
+        if let Some(color) = entry.color {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled("●", Style::default().fg(color)));
+        }
+        if !entry.detail.is_empty() {
+            spans.push(Span::styled(
+                format!("  {}", entry.detail),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    if picker.visible.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No profiles or recent connections. Type a file path or connection string.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the selection on screen
+    let height = inner_chunks[1].height as usize;
+    let scroll = (selected_line + 1).saturating_sub(height);
+    frame.render_widget(
+        Paragraph::new(lines).scroll((scroll as u16, 0)),
+        inner_chunks[1],
+    );
+
+    let status = match (&picker.connecting, &picker.error) {
+        (Some(target), _) => Span::styled(
+            format!(" Connecting to {}…", target),
+            Style::default().fg(Color::Yellow),
+        ),
+        (None, Some(error)) => Span::styled(format!(" {}", error), Style::default().fg(Color::Red)),
+        (None, None) => Span::styled(
+            " Type to filter or enter a connection string · ↑↓ Select · Enter Open · Esc Quit",
+            Style::default().fg(Color::DarkGray),
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(status)).style(Style::default().bg(Color::Black)),
+        chunks[2],
+    );
+}
@@ -1,4 +1,6 @@
 use crate::app::{App, Panel};
+use crate::sql::first_keyword;
+use crate::value::CellValue;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
@@ -9,12 +11,45 @@ use unicode_width::UnicodeWidthStr;
 const MAX_CELL_WIDTH: usize = 40;
 
 pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
-    let result = match &app.result {
+    let tab = &app.tabs[app.active_tab];
+    let area = if tab.results.len() > 1 || tab.statements.len() > 1 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+        render_statement_list(frame, app, chunks[0]);
+        chunks[1]
+    } else {
+        area
+    };
+
+    let tab = &app.tabs[app.active_tab];
+    let result = match tab.results.get(tab.selected_result) {
         Some(r) => r,
         None => {
+            let msg = match &tab.query {
+                Some(query) if query.statement_count > 1 => format!(
+                    "Running statement {} of {}… {:.1}s",
+                    tab.results.len() + 1,
+                    query.statement_count,
+                    query.started.elapsed().as_secs_f64()
+                ),
+                Some(query) => format!(
+                    "Running query… {:.1}s",
+                    query.started.elapsed().as_secs_f64()
+                ),
+                None => {
+                    let hint = Paragraph::new(Line::from(Span::styled(
+                        "Press F5 to execute query",
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                    frame.render_widget(hint, area);
+                    return;
+                }
+            };
             let hint = Paragraph::new(Line::from(Span::styled(
-                "Press F5 to execute query",
-                Style::default().fg(Color::DarkGray),
+                msg,
+                Style::default().fg(Color::Yellow),
             )));
             frame.render_widget(hint, area);
             return;
@@ -39,16 +74,29 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // No results
     if result.columns.is_empty() {
-        let msg = if result.row_count == 0 {
-            "Query executed successfully (no results)"
-        } else {
-            "Query executed successfully"
-        };
-        let hint = Paragraph::new(Line::from(Span::styled(
-            msg,
-            Style::default().fg(Color::Green),
-        )));
-        frame.render_widget(hint, area);
+        let green = Style::default().fg(Color::Green);
+        let mut lines = vec![Line::from(Span::styled(
+            "Query executed successfully",
+            green,
+        ))];
+        match result.rows_affected {
+            Some(1) => lines.push(Line::from(Span::styled("1 row affected", green))),
+            Some(n) => lines.push(Line::from(Span::styled(
+                format!("{} rows affected", n),
+                green,
+            ))),
+            None => {}
+        }
+        if let Some(rowid) = result.last_insert_rowid {
+            lines.push(Line::from(format!("Last insert rowid: {}", rowid)));
+        }
+        if let Some(tag) = &result.command_tag {
+            lines.push(Line::from(Span::styled(
+                tag.clone(),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), area);
         return;
     }
 
@@ -70,14 +118,14 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     let mut col_widths: Vec<usize> = result
         .columns
         .iter()
-        .map(|c| c.width().min(MAX_CELL_WIDTH))
+        .map(|c| c.name.width().min(MAX_CELL_WIDTH))
         .collect();
 
     // Consider data widths from current page
     for row in page_rows {
         for (i, cell) in row.iter().enumerate() {
             if i < col_widths.len() {
-                col_widths[i] = col_widths[i].max(cell.width().min(MAX_CELL_WIDTH));
+                col_widths[i] = col_widths[i].max(cell.to_string().width().min(MAX_CELL_WIDTH));
             }
         }
     }
@@ -152,7 +200,10 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 
     // Header
-    let visible_columns: Vec<String> = result.columns[col_start..col_end].to_vec();
+    let visible_columns: Vec<CellValue> = result.columns[col_start..col_end]
+        .iter()
+        .map(|c| CellValue::Text(c.name.clone()))
+        .collect();
     let selected_col_in_view = if is_focused
         && app.result_selected_col >= col_start
         && app.result_selected_col < col_end
@@ -194,9 +245,9 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
             None
         };
 
-        let visible_cells: Vec<String> = row[col_start..col_end.min(row.len())].to_vec();
+        let visible_cells = &row[col_start..col_end.min(row.len())];
         let row_spans = build_row_spans(
-            &visible_cells,
+            visible_cells,
             visible_col_widths,
             false,
             Some(is_selected_row),
@@ -209,8 +260,60 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+/// One entry per statement of the last script, with the selected one highlighted.
+fn render_statement_list(frame: &mut Frame, app: &App, area: Rect) {
+    let tab = app.current_tab();
+    let mut items: Vec<(String, Style)> = tab
+        .statements
+        .iter()
+        .enumerate()
+        .map(|(i, sql)| {
+            let verb = first_keyword(sql);
+            let (summary, color) = match tab.results.get(i) {
+                Some(r) if r.error.is_some() => ("error".to_string(), Color::Red),
+                Some(r) if r.columns.is_empty() => match r.rows_affected {
+                    Some(n) => (format!("{} affected", n), Color::Green),
+                    None => ("ok".to_string(), Color::Green),
+                },
+                Some(r) if r.has_more => (format!("{}+ rows", r.row_count), Color::White),
+                Some(r) => (format!("{} rows", r.row_count), Color::White),
+                None if tab.query.is_some() && i == tab.results.len() => {
+                    ("running".to_string(), Color::Yellow)
+                }
+                None => ("skipped".to_string(), Color::DarkGray),
+            };
+            let mut style = Style::default().fg(color);
+            if i == tab.selected_result {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            (format!(" {} {} {} ", i + 1, verb, summary), style)
+        })
+        .collect();
+
+    // Drop entries from the left until the selected one fits
+    let mut start = 0;
+    while start < tab.selected_result
+        && items[start..=tab.selected_result.min(items.len() - 1)]
+            .iter()
+            .map(|(text, _)| text.width() + 1)
+            .sum::<usize>()
+            > (area.width as usize).saturating_sub(2)
+    {
+        start += 1;
+    }
+    let mut spans = Vec::new();
+    if start > 0 {
+        spans.push(Span::styled("‹ ", Style::default().fg(Color::DarkGray)));
+    }
+    for (text, style) in items.drain(start..) {
+        spans.push(Span::styled(text, style));
+        spans.push(Span::raw(" "));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
 pub fn render_cell_detail(frame: &mut Frame, app: &mut App) {
     let (title, cell_value_owned);
+    let json = get_detail_json(app);
 
     if app.show_row_detail {
         cell_value_owned = match &app.row_detail_json {
@@ -224,8 +327,7 @@ pub fn render_cell_detail(frame: &mut Frame, app: &mut App) {
             None => return,
         };
         cell_value_owned = cell_value.to_string();
-        let json_result: Result<serde_json::Value, _> = serde_json::from_str(&cell_value_owned);
-        title = if json_result.is_ok() {
+        title = if json.is_some() {
             format!(" {} (JSON) ", col_name)
         } else {
             format!(" {} ", col_name)
@@ -248,8 +350,7 @@ pub fn render_cell_detail(frame: &mut Frame, app: &mut App) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let json_result: Result<serde_json::Value, _> = serde_json::from_str(&cell_value_owned);
-    if let Ok(json) = json_result {
+    if let Some(json) = json {
         render_json_tree(frame, app, inner, &json);
     } else {
         let content = Paragraph::new(cell_value_owned)
@@ -522,42 +623,34 @@ fn truncate_json_string(s: &str, max_len: usize) -> String {
     }
 }
 
-fn get_detail_json_str(app: &App) -> Option<String> {
+fn get_detail_json(app: &App) -> Option<serde_json::Value> {
     if app.show_row_detail {
-        return app.row_detail_json.clone();
+        return serde_json::from_str(app.row_detail_json.as_deref()?).ok();
     }
-    app.get_selected_cell().map(|(_, v)| v.to_string())
+    app.get_selected_cell()
+        .and_then(|(_, v)| v.as_json_document())
 }
 
 pub fn get_json_line_count(app: &App) -> usize {
-    let cell_value = match get_detail_json_str(app) {
-        Some(v) => v,
-        None => return 0,
-    };
-
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&cell_value) {
-        let mut lines = Vec::new();
-        build_json_lines(&mut lines, &json, "$", 0, &app.json_expanded);
-        lines.len()
-    } else {
-        0
+    match get_detail_json(app) {
+        Some(json) => {
+            let mut lines = Vec::new();
+            build_json_lines(&mut lines, &json, "$", 0, &app.json_expanded);
+            lines.len()
+        }
+        None => 0,
     }
 }
 
 pub fn get_selected_json_path(app: &App) -> Option<String> {
-    let cell_value = get_detail_json_str(app)?;
-
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&cell_value) {
-        let mut lines = Vec::new();
-        build_json_lines(&mut lines, &json, "$", 0, &app.json_expanded);
-        lines.get(app.json_selected).map(|l| l.path.clone())
-    } else {
-        None
-    }
+    let json = get_detail_json(app)?;
+    let mut lines = Vec::new();
+    build_json_lines(&mut lines, &json, "$", 0, &app.json_expanded);
+    lines.get(app.json_selected).map(|l| l.path.clone())
 }
 
 fn build_row_spans(
-    cells: &[String],
+    cells: &[CellValue],
     widths: &[usize],
     is_header: bool,
     is_selected_row: Option<bool>,
@@ -568,8 +661,13 @@ fn build_row_spans(
 
     for (i, cell) in cells.iter().enumerate() {
         let width = widths.get(i).copied().unwrap_or(10);
-        let truncated = truncate_cell(cell, width);
-        let padded = format!(" {:<width$} ", truncated, width = width);
+        let truncated = truncate_cell(&cell.to_string(), width);
+        // Right-align numbers so digits line up
+        let padded = if cell.is_numeric() && !is_header {
+            format!(" {:>width$} ", truncated, width = width)
+        } else {
+            format!(" {:<width$} ", truncated, width = width)
+        };
 
         let is_selected_cell = selected_col == Some(i);
 
@@ -588,8 +686,10 @@ fn build_row_spans(
             s
         } else if is_selected_row == Some(true) {
             Style::default().fg(Color::White).bg(Color::DarkGray)
-        } else if cell == "NULL" {
-            Style::default().fg(Color::DarkGray)
+        } else if cell.is_null() {
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC)
         } else {
             Style::default().fg(Color::White)
         };
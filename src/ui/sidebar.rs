@@ -1,28 +1,61 @@
-use crate::app::{App, SidebarSection};
+use crate::app::{Connection, SidebarSection};
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 
-pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
+pub fn render(frame: &mut Frame, connection: &mut Connection, area: Rect) {
     let mut lines: Vec<Line> = Vec::new();
     let mut current_section: Option<SidebarSection> = None;
+    let mut current_schema: Option<&str> = None;
+    let mut under_schema_header = false;
     let mut item_index = 0;
     let visible_height = area.height as usize;
     let mut selected_line: Option<usize> = None;
+    let width = area.width as usize;
+    let selected_style = Style::default()
+        .fg(Color::Black)
+        .bg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+
+    for (idx, item) in connection.sidebar_items.iter().enumerate() {
+        // Schema header (Postgres only)
+        if let Some(schema) = item.schema.as_deref()
+            && current_schema != Some(schema)
+        {
+            current_schema = Some(schema);
+            current_section = None;
+
+            if item_index > 0 {
+                lines.push(Line::from(""));
+                item_index += 1;
+            }
+
+            lines.push(Line::from(Span::styled(
+                format!("◆ {}", schema),
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            item_index += 1;
+            under_schema_header = true;
+        }
 
-    for (idx, item) in app.sidebar_items.iter().enumerate() {
         // Section header
         if current_section != Some(item.section) {
             current_section = Some(item.section);
             let section_name = match item.section {
                 SidebarSection::Tables => "TABLES",
                 SidebarSection::Views => "VIEWS",
+                SidebarSection::MaterializedViews => "MATERIALIZED VIEWS",
                 SidebarSection::Indexes => "INDEXES",
+                SidebarSection::Sequences => "SEQUENCES",
+                SidebarSection::Functions => "FUNCTIONS",
+                SidebarSection::Types => "TYPES",
             };
 
-            if item_index > 0 {
+            if item_index > 0 && !under_schema_header {
                 lines.push(Line::from(""));
                 item_index += 1;
             }
@@ -34,14 +67,15 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
                     .add_modifier(Modifier::BOLD),
             )));
             item_index += 1;
+            under_schema_header = false;
         }
 
         // Item
-        let is_selected = idx == app.sidebar_selected;
+        let is_selected = idx == connection.sidebar_selected && connection.sidebar_group.is_none();
         if is_selected {
             selected_line = Some(item_index);
         }
-        let prefix = if item.children.is_empty() {
+        let prefix = if !item.has_children() {
             "  "
         } else if item.is_expanded {
             "▾ "
@@ -50,19 +84,12 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
         };
 
         let style = if is_selected {
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Cyan)
-                .add_modifier(Modifier::BOLD)
+            selected_style
         } else {
             Style::default().fg(Color::White)
         };
 
-        let name = if item.name.len() > area.width as usize - 4 {
-            format!("{}…", &item.name[..area.width as usize - 5])
-        } else {
-            item.name.clone()
-        };
+        let name = truncate(&item.name, width.saturating_sub(4));
 
         lines.push(Line::from(Span::styled(
             format!("{}{}", prefix, name),
@@ -70,17 +97,48 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
         )));
         item_index += 1;
 
-        // Expanded children (columns)
+        // Expanded children (columns), then groups such as constraints
         if item.is_expanded {
+            let child_style = Style::default().fg(Color::DarkGray);
             for child in &item.children {
-                let child_style = Style::default().fg(Color::DarkGray);
-                let child_text = if child.len() > area.width as usize - 6 {
-                    format!("    {}…", &child[..area.width as usize - 7])
+                let child_text = format!("    {}", truncate(child, width.saturating_sub(6)));
+                lines.push(Line::from(Span::styled(child_text, child_style)));
+                item_index += 1;
+            }
+
+            for (group_idx, group) in item.groups.iter().enumerate() {
+                let is_selected = idx == connection.sidebar_selected
+                    && connection.sidebar_group == Some(group_idx);
+                if is_selected {
+                    selected_line = Some(item_index);
+                }
+                let marker = if group.is_expanded { "▾ " } else { "▸ " };
+                let style = if is_selected {
+                    selected_style
                 } else {
-                    format!("    {}", child)
+                    Style::default().fg(Color::Gray)
                 };
-                lines.push(Line::from(Span::styled(child_text, child_style)));
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        format!(
+                            "{}{}",
+                            marker,
+                            truncate(&group.label, width.saturating_sub(6))
+                        ),
+                        style,
+                    ),
+                ]));
                 item_index += 1;
+
+                if group.is_expanded {
+                    for child in &group.children {
+                        let child_text =
+                            format!("      {}", truncate(child, width.saturating_sub(8)));
+                        lines.push(Line::from(Span::styled(child_text, child_style)));
+                        item_index += 1;
+                    }
+                }
             }
         }
     }
@@ -94,13 +152,23 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Update scroll (in line units) to keep selected item visible
     if let Some(sel_line) = selected_line {
-        if sel_line < app.sidebar_scroll {
-            app.sidebar_scroll = sel_line;
-        } else if sel_line >= app.sidebar_scroll + visible_height {
-            app.sidebar_scroll = sel_line.saturating_sub(visible_height - 1);
+        if sel_line < connection.sidebar_scroll {
+            connection.sidebar_scroll = sel_line;
+        } else if sel_line >= connection.sidebar_scroll + visible_height {
+            connection.sidebar_scroll = sel_line.saturating_sub(visible_height - 1);
         }
     }
 
-    let paragraph = Paragraph::new(lines).scroll((app.sidebar_scroll as u16, 0));
+    let paragraph = Paragraph::new(lines).scroll((connection.sidebar_scroll as u16, 0));
     frame.render_widget(paragraph, area);
 }
+
+/// Cuts text to `width` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", kept)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,202 @@
+use crate::db::Dialect;
+use std::fmt;
+
+/// A single result cell, keeping the type the database reported for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// An exact number such as NUMERIC or DECIMAL, kept as its digits
+    Decimal(String),
+    Text(String),
+    Bytes(Vec<u8>),
+    Json(serde_json::Value),
+    Timestamp(chrono::NaiveDateTime),
+    TimestampTz(chrono::DateTime<chrono::Utc>),
+    Date(chrono::NaiveDate),
+    Time(chrono::NaiveTime),
+    /// A value the driver could not decode, holding the database type name
+    Unsupported(String),
+}
+
+impl CellValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, CellValue::Null)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            CellValue::Int(_) | CellValue::Float(_) | CellValue::Decimal(_)
+        )
+    }
+
+    /// Converts the value for the JSON row view, keeping numbers, booleans and NULL typed.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            CellValue::Null => serde_json::Value::Null,
+            CellValue::Bool(b) => serde_json::Value::Bool(*b),
+            CellValue::Int(i) => serde_json::Value::from(*i),
+            CellValue::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or_else(|| serde_json::Value::String(f.to_string())),
+            // A number only when it fits exactly; more digits stay a string
+            CellValue::Decimal(d) => {
+                if let Ok(i) = d.parse::<i64>() {
+                    return serde_json::Value::from(i);
+                }
+                let scale = d.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+                d.parse::<f64>()
+                    .ok()
+                    .filter(|f| format!("{:.*}", scale, f) == *d)
+                    .and_then(serde_json::Number::from_f64)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or_else(|| serde_json::Value::String(d.clone()))
+            }
+            CellValue::Json(v) => v.clone(),
+            CellValue::Text(_) => self
+                .as_json_document()
+                .unwrap_or_else(|| serde_json::Value::String(self.to_string())),
+            _ => serde_json::Value::String(self.to_string()),
+        }
+    }
+
+    /// Writes the value as a SQL literal in `dialect` for a generated query. NULL has
+    /// none, since nothing compares equal to it.
+    pub fn to_sql_literal(&self, dialect: Dialect) -> Option<String> {
+        let quote = |s: &str| {
+            let s = s.replace('\'', "''");
+            if dialect.backslash_escapes {
+                format!("'{}'", s.replace('\\', "\\\\"))
+            } else {
+                format!("'{}'", s)
+            }
+        };
+        Some(match self {
+            CellValue::Null | CellValue::Unsupported(_) => return None,
+            CellValue::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            CellValue::Int(i) => i.to_string(),
+            // NaN and Infinity are only understood quoted
+            CellValue::Float(v) if v.is_finite() => v.to_string(),
+            CellValue::Float(v) => quote(&float_keyword(*v)),
+            CellValue::Decimal(d) if d.parse::<f64>().is_ok_and(f64::is_finite) => d.clone(),
+            CellValue::Bytes(b) => {
+                let hex: String = b.iter().map(|byte| format!("{:02x}", byte)).collect();
+                if dialect.bytea_literals {
+                    format!("'\\x{}'::bytea", hex)
+                } else {
+                    format!("X'{}'", hex)
+                }
+            }
+            CellValue::Timestamp(v) => quote(&v.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            CellValue::TimestampTz(v) => quote(&v.to_rfc3339()),
+            _ => quote(&self.to_string()),
+        })
+    }
+
+    /// Returns the value as a JSON object or array if it holds one, for the JSON viewer.
+    pub fn as_json_document(&self) -> Option<serde_json::Value> {
+        match self {
+            CellValue::Json(v) => Some(v.clone()),
+            CellValue::Text(s) => {
+                let trimmed = s.trim_start();
+                if trimmed.starts_with('{') || trimmed.starts_with('[') {
+                    serde_json::from_str(s).ok()
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How Postgres, MySQL and SQLite spell a float that isn't a number, once quoted.
+fn float_keyword(v: f64) -> String {
+    match v {
+        v if v.is_nan() => "NaN".to_string(),
+        v if v > 0.0 => "Infinity".to_string(),
+        _ => "-Infinity".to_string(),
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Null => f.write_str("NULL"),
+            CellValue::Bool(b) => write!(f, "{}", b),
+            CellValue::Int(i) => write!(f, "{}", i),
+            CellValue::Float(v) => write!(f, "{}", v),
+            CellValue::Decimal(d) => f.write_str(d),
+            CellValue::Text(s) => f.write_str(s),
+            CellValue::Bytes(b) => write!(f, "[BLOB {} bytes]", b.len()),
+            CellValue::Json(v) => write!(f, "{}", v),
+            CellValue::Timestamp(v) => write!(f, "{}", v.format("%Y-%m-%dT%H:%M:%S.%3f")),
+            CellValue::TimestampTz(v) => {
+                f.write_str(&v.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+            }
+            CellValue::Date(v) => write!(f, "{}", v.format("%Y-%m-%d")),
+            CellValue::Time(v) => write!(f, "{}", v.format("%H:%M:%S")),
+            CellValue::Unsupported(type_name) => write!(f, "[{} UNSUPPORTED]", type_name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialect(bytea_literals: bool, backslash_escapes: bool) -> Dialect {
+        Dialect {
+            name: "test",
+            savepoints: true,
+            backslash_escapes,
+            bytea_literals,
+            identifier_quote: '"',
+        }
+    }
+
+    #[test]
+    fn writes_bytes_as_each_dialect_reads_them() {
+        let bytes = CellValue::Bytes(vec![0xde, 0xad]);
+        assert_eq!(
+            bytes.to_sql_literal(dialect(true, false)).as_deref(),
+            Some("'\\xdead'::bytea")
+        );
+        assert_eq!(
+            bytes.to_sql_literal(dialect(false, false)).as_deref(),
+            Some("X'dead'")
+        );
+    }
+
+    #[test]
+    fn quotes_what_isnt_a_plain_number() {
+        let sql = |value: CellValue| value.to_sql_literal(dialect(false, false));
+        assert_eq!(sql(CellValue::Float(1.5)).as_deref(), Some("1.5"));
+        assert_eq!(sql(CellValue::Float(f64::NAN)).as_deref(), Some("'NaN'"));
+        assert_eq!(
+            sql(CellValue::Float(f64::NEG_INFINITY)).as_deref(),
+            Some("'-Infinity'")
+        );
+        assert_eq!(
+            sql(CellValue::Decimal("Infinity".to_string())).as_deref(),
+            Some("'Infinity'")
+        );
+        assert_eq!(sql(CellValue::Null), None);
+    }
+
+    #[test]
+    fn escapes_backslashes_where_strings_use_them() {
+        let text = CellValue::Text("it's a\\b".to_string());
+        assert_eq!(
+            text.to_sql_literal(dialect(false, false)).as_deref(),
+            Some("'it''s a\\b'")
+        );
+        assert_eq!(
+            text.to_sql_literal(dialect(false, true)).as_deref(),
+            Some("'it''s a\\\\b'")
+        );
+    }
+}
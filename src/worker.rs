@@ -0,0 +1,294 @@
+use crate::db::{CancelHandle, Database, QueryResult, ResultColumn, TransactionState};
+use crate::value::CellValue;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Instant;
+
+/// Locks the shared database, recovering it if a worker panicked while holding it.
+pub fn lock_db(db: &Mutex<Database>) -> MutexGuard<'_, Database> {
+    db.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// `COUNT(*)` queries running on a background thread, which sends back all the counts
+/// at once.
+pub struct CountHandle {
+    counts: Receiver<Vec<Option<u64>>>,
+    state: Arc<Mutex<CountState>>,
+    cancel: CancelHandle,
+}
+
+#[derive(Default)]
+struct CountState {
+    /// A count query is on the database, so a cancel would stop it
+    running: bool,
+    stopped: bool,
+}
+
+impl CountHandle {
+    /// Starts the counts, `None` where there is no query or it fails.
+    pub fn spawn(
+        db: Arc<Mutex<Database>>,
+        cancel: CancelHandle,
+        queries: Vec<Option<String>>,
+    ) -> Self {
+        let (sender, counts) = mpsc::channel();
+        let state = Arc::new(Mutex::new(CountState::default()));
+        let shared = Arc::clone(&state);
+        thread::spawn(move || {
+            let db = lock_db(&db);
+            let mut counts = Vec::new();
+            for sql in &queries {
+                let Some(sql) = sql else {
+                    counts.push(None);
+                    continue;
+                };
+                {
+                    let mut state = lock_state(&shared);
+                    if state.stopped {
+                        break;
+                    }
+                    state.running = true;
+                }
+                let count = db.count_rows(sql);
+                // Waits here while a cancel is on its way, so it can't reach a later query
+                lock_state(&shared).running = false;
+                counts.push(count);
+            }
+            let _ = sender.send(counts);
+        });
+        Self {
+            counts,
+            state,
+            cancel,
+        }
+    }
+
+    /// The counts once they're done, or an empty list if the thread is gone.
+    pub fn try_counts(&self) -> Option<Vec<Option<u64>>> {
+        match self.counts.try_recv() {
+            Ok(counts) => Some(counts),
+            Err(TryRecvError::Disconnected) => Some(vec![]),
+            Err(TryRecvError::Empty) => None,
+        }
+    }
+
+    /// Skips the counts not started yet and cancels the one running, if any, in the
+    /// background. The counts aren't sent back until the database has the cancel.
+    pub fn stop(&self) {
+        let (state, cancel) = (Arc::clone(&self.state), self.cancel.clone());
+        thread::spawn(move || {
+            let mut state = lock_state(&state);
+            state.stopped = true;
+            if state.running {
+                cancel.cancel_and_wait();
+            }
+        });
+    }
+}
+
+fn lock_state(state: &Mutex<CountState>) -> MutexGuard<'_, CountState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Progress reported by a query worker, per statement of the script it runs.
+#[derive(Debug)]
+pub enum QueryEvent {
+    /// A batch of rows; `parked` is set when the worker holds the cursor open for more
+    Rows {
+        statement: usize,
+        columns: Vec<ResultColumn>,
+        rows: Vec<Vec<CellValue>>,
+        parked: bool,
+    },
+    /// The statement's cursor is closed
+    Finished {
+        statement: usize,
+        result: QueryResult,
+    },
+    /// The worker has released the database, having stopped early if a statement failed.
+    /// Carries the connection's transaction state, unless the worker died.
+    Done {
+        transaction: Option<TransactionState>,
+    },
+}
+
+/// A script executing on a background thread.
+///
+/// Statements run in order, each read up to `max_rows`. The last one keeps its cursor
+/// open after the first batch and fetches more rows when asked to, holding the database
+/// until the handle is dropped or the rows run out.
+#[derive(Debug)]
+pub struct QueryHandle {
+    pub started: Instant,
+    pub statement_count: usize,
+    cancel: CancelHandle,
+    cancelled: bool,
+    fetching: bool,
+    finished: usize,
+    stopped: bool,
+    events: Receiver<QueryEvent>,
+    fetch: Sender<usize>,
+}
+
+impl QueryHandle {
+    pub fn spawn(
+        db: Arc<Mutex<Database>>,
+        cancel: CancelHandle,
+        statements: Vec<String>,
+        first_batch: usize,
+        max_rows: usize,
+        begin_transaction: bool,
+    ) -> Self {
+        let (event_sender, events) = mpsc::channel();
+        let (fetch, fetch_requests) = mpsc::channel::<usize>();
+        let statement_count = statements.len();
+
+        thread::spawn(move || {
+            let db = lock_db(&db);
+            if begin_transaction
+                && db.transaction_state() == TransactionState::Idle
+                && let Err(e) = db.begin_transaction()
+            {
+                let result = QueryResult::error(
+                    format!("Failed to begin transaction: {}", e),
+                    Default::default(),
+                );
+                let _ = event_sender.send(QueryEvent::Finished {
+                    statement: 0,
+                    result,
+                });
+                let _ = event_sender.send(QueryEvent::Done {
+                    transaction: Some(db.transaction_state()),
+                });
+                return;
+            }
+            for (statement, sql) in statements.iter().enumerate() {
+                let lazy = statement + 1 == statement_count;
+                let mut remaining = max_rows;
+                let mut requested = if lazy {
+                    first_batch.min(remaining)
+                } else {
+                    remaining
+                };
+                let result = db.stream_query(sql, requested, &mut |columns, rows| {
+                    remaining = remaining.saturating_sub(rows.len());
+                    let parked = lazy && rows.len() >= requested && remaining > 0;
+                    let event = QueryEvent::Rows {
+                        statement,
+                        columns: columns.to_vec(),
+                        rows,
+                        parked,
+                    };
+                    if event_sender.send(event).is_err() {
+                        return 0;
+                    }
+                    if !parked {
+                        requested = remaining;
+                        return requested;
+                    }
+                    // Park the cursor until the UI wants more; a dropped handle closes it
+                    requested = match fetch_requests.recv() {
+                        Ok(n) => n.min(remaining),
+                        Err(_) => 0,
+                    };
+                    requested
+                });
+                let failed = result.error.is_some();
+                // The receiver is gone if the tab was closed while the query ran
+                if event_sender
+                    .send(QueryEvent::Finished { statement, result })
+                    .is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+            let _ = event_sender.send(QueryEvent::Done {
+                transaction: Some(db.transaction_state()),
+            });
+        });
+
+        Self {
+            started: Instant::now(),
+            statement_count,
+            cancel,
+            cancelled: false,
+            fetching: true,
+            finished: 0,
+            stopped: false,
+            events,
+            fetch,
+        }
+    }
+
+    /// True while the worker is executing or fetching, as opposed to parked on an open cursor.
+    pub fn is_busy(&self) -> bool {
+        self.fetching
+    }
+
+    /// Asks the parked worker for more rows.
+    pub fn fetch_more(&mut self, rows: usize) {
+        if !self.fetching && self.fetch.send(rows).is_ok() {
+            self.fetching = true;
+        }
+    }
+
+    /// Asks the database to abort the query; the worker still reports back with an error.
+    pub fn cancel(&mut self) {
+        if !self.cancelled {
+            self.cancelled = true;
+            self.cancel.cancel();
+        }
+    }
+
+    /// Index of the statement the worker is on.
+    pub fn current_statement(&self) -> usize {
+        self.finished.min(self.statement_count.saturating_sub(1))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Returns the next event from the worker, without blocking.
+    pub fn try_event(&mut self) -> Option<QueryEvent> {
+        if self.stopped {
+            return Some(QueryEvent::Done { transaction: None });
+        }
+        let event = match self.events.try_recv() {
+            Ok(QueryEvent::Finished { statement, result })
+                if self.cancelled && result.error.is_some() =>
+            {
+                QueryEvent::Finished {
+                    statement,
+                    result: QueryResult::error(
+                        format!(
+                            "Query cancelled after {:.1}s",
+                            self.started.elapsed().as_secs_f64()
+                        ),
+                        result.execution_time,
+                    ),
+                }
+            }
+            Ok(event) => event,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                self.stopped = true;
+                QueryEvent::Finished {
+                    statement: self.finished,
+                    result: QueryResult::error(
+                        "Query worker stopped unexpectedly".to_string(),
+                        self.started.elapsed(),
+                    ),
+                }
+            }
+        };
+        match event {
+            QueryEvent::Rows { parked, .. } => self.fetching = !parked,
+            QueryEvent::Finished { .. } => self.finished += 1,
+            QueryEvent::Done { .. } => self.stopped = true,
+        }
+        Some(event)
+    }
+}